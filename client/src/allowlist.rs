@@ -0,0 +1,63 @@
+use std::{fs, path::Path, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use solana_program::pubkey::Pubkey;
+use voila_nft::nft::allowlist::{build_proof, build_tree, AllowlistProof};
+
+/// Merkle tree of allowlisted wallets, the root goes into `CommonNFTInfo::allowlist_root`.
+pub struct Allowlist {
+    entries: Vec<(Pubkey, u16)>,
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl Allowlist {
+    pub fn new(entries: Vec<(Pubkey, u16)>) -> Result<Self> {
+        if entries.is_empty() {
+            return Err(anyhow!("allowlist is empty"));
+        }
+
+        let layers = build_tree(&entries);
+        Ok(Self { entries, layers })
+    }
+
+    /// Read entries from a CSV of `address[,allowance]` lines, allowance 0 or absent means no own limit.
+    pub fn from_csv<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+
+        let mut entries = Vec::new();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split(',').map(str::trim);
+            let address = fields.next().unwrap_or_default();
+            let wallet = match Pubkey::from_str(address) {
+                Ok(wallet) => wallet,
+                // skip the header line
+                Err(_) if i == 0 => continue,
+                Err(e) => return Err(anyhow!("line {}: invalid address {}: {}", i + 1, address, e)),
+            };
+            let allowance = match fields.next() {
+                Some(allowance) if !allowance.is_empty() => allowance
+                    .parse::<u16>()
+                    .map_err(|e| anyhow!("line {}: invalid allowance {}: {}", i + 1, allowance, e))?,
+                _ => 0,
+            };
+            entries.push((wallet, allowance));
+        }
+
+        Self::new(entries)
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.layers.last().unwrap()[0]
+    }
+
+    pub fn proof(&self, wallet: &Pubkey) -> Option<AllowlistProof> {
+        let index = self.entries.iter().position(|(entry, _)| entry == wallet)?;
+
+        Some(build_proof(&self.layers, index, self.entries[index].1))
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::{anyhow, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_program::pubkey::Pubkey;
+use voila_nft::{
+    nft::auction::{NFTAuction, BidHistoryPage, BidInfo, BID_HISTORY_PAGE_LEN},
+    pda::get_bid_history_page_pda,
+    Packer,
+};
+
+/// Limit of accounts in one `getMultipleAccounts` request
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Reconstruct the full bid history of an auction from its history pages, oldest first.
+pub fn get_bid_history(client: &RpcClient, nft_auction: &Pubkey) -> Result<Vec<BidInfo>> {
+    let data = client.get_account_data(nft_auction)?;
+    let auction = NFTAuction::unpack(&data)?;
+    if auction.bid_count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let last_page = (auction.bid_count - 1) / BID_HISTORY_PAGE_LEN as u32;
+    let pages = (0..=last_page)
+        .map(|page| get_bid_history_page_pda(nft_auction, page, &voila_nft::ID).0)
+        .collect::<Vec<_>>();
+
+    let mut bid_infos = Vec::with_capacity(auction.bid_count as usize);
+    for (chunk, chunk_pages) in pages.chunks(MAX_MULTIPLE_ACCOUNTS).enumerate() {
+        for (i, account) in client.get_multiple_accounts(chunk_pages)?.into_iter().enumerate() {
+            let page = chunk * MAX_MULTIPLE_ACCOUNTS + i;
+            let account = account.ok_or_else(|| anyhow!("bid history page {} not found", page))?;
+            let history_page = BidHistoryPage::unpack(&account.data)?;
+            bid_infos.extend(history_page.bid_infos);
+        }
+    }
+
+    Ok(bid_infos)
+}
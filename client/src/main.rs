@@ -1,3 +1,5 @@
+mod allowlist;
+mod history;
 mod transaction;
 
 use std::str::FromStr;
@@ -37,6 +39,13 @@ fn main() {
     //     admin.pubkey(),
     //     110000000,
     //     205,
+    //     None,
+    //     None,
+    //     None,
+    //     None,
+    //     None,
+    //     None,
+    //     None,
     //     "primary".to_string(),
     //     "https://voila.com".to_string(),
     //     blockhash,
@@ -47,6 +56,13 @@ fn main() {
     //     admin.pubkey(),
     //     990000000,
     //     100,
+    //     None,
+    //     None,
+    //     None,
+    //     None,
+    //     None,
+    //     None,
+    //     None,
     //     "senior".to_string(),
     //     "https://voila.com".to_string(),
     //     blockhash,
@@ -55,10 +71,21 @@ fn main() {
     let tx = transaction::do_create_nft_auction(
         &admin,
         4,
+        admin.pubkey(),
         1650855600,
         1650857400,
         1_000_000_000,
         100_000_000,
+        0,
+        1,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
         "auction".to_string(),
         "https://voila.com".to_string(),
         blockhash,
@@ -74,14 +101,21 @@ fn main() {
     //     COMMON_NFT_PUBKEY,
     //     admin.pubkey(),
     //     5,
+    //     None,
+    //     None,
+    //     None,
+    //     None,
+    //     false,
     //     blockhash,
     // );
 
     // let tx = transaction::do_bid_in_nft_auction(
     //     &user,
     //     NFT_AUCTION,
+    //     0,
     //     None,
     //     100_000_000,
+    //     None,
     //     blockhash,
     // );
 
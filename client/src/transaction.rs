@@ -1,6 +1,14 @@
 use solana_program::{pubkey::Pubkey, hash::Hash};
 use solana_sdk::{signature::Keypair, transaction::Transaction, signer::Signer};
-use voila_nft::pda::get_common_nft_mint_pda;
+use voila_nft::nft::{
+    Presale,
+    Royalty,
+    auction::{BidExtension, ReservePrice, Consignment, NFTAuctionUpdate},
+    dutch_auction::PriceDecay,
+    sealed_auction::{SealedAuctionKind, sealed_bid_commitment},
+};
+
+use crate::allowlist::Allowlist;
 
 pub fn do_create_key_info(
     admin_authority: &Keypair,
@@ -22,41 +30,163 @@ pub fn do_create_key_info(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn do_create_nft_auction(
     admin_authority: &Keypair,
     sn: u16,
+    receipt: Pubkey,
     start_time: i64,
     end_time: i64,
     base_price: u64,
     price_raise: u64,
+    min_raise_bps: u16,
+    winners: u8,
+    bid_extension: Option<BidExtension>,
+    reserve_price: Option<ReservePrice>,
+    buy_now_price: Option<u64>,
+    payment_mint: Option<Pubkey>,
+    consignment: Option<(&Keypair, Consignment)>,
+    crank_reward: Option<u64>,
+    key_gate: Option<Pubkey>,
+    royalty: Option<Royalty>,
     name: String,
     uri: String,
     blockhash: Hash,
 ) -> Transaction {
+    let mut signers = vec![admin_authority];
+    if let Some((seller, _)) = consignment {
+        signers.push(seller);
+    }
+
     Transaction::new_signed_with_payer(
         &[
             voila_nft::instruction::create_nft_auction(
                 admin_authority.pubkey(),
                 sn,
+                receipt,
                 start_time,
                 end_time,
                 base_price,
                 price_raise,
+                min_raise_bps,
+                winners,
+                bid_extension,
+                reserve_price,
+                buy_now_price,
+                payment_mint,
+                consignment.map(|(_, consignment)| consignment),
+                crank_reward,
+                key_gate,
+                royalty,
                 name,
                 uri,
             ),
         ],
         Some(&admin_authority.pubkey()),
+        &signers,
+        blockhash,
+    )
+}
+
+pub fn do_update_nft_auction(
+    admin_authority: &Keypair,
+    auction_info: Pubkey,
+    update: NFTAuctionUpdate,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::update_nft_auction(
+                auction_info,
+                admin_authority.pubkey(),
+                update,
+            ),
+        ],
+        Some(&admin_authority.pubkey()),
         &[admin_authority],
         blockhash,
     )
 }
 
+pub fn do_migrate_nft_auction(
+    admin_authority: &Keypair,
+    auction_info: Pubkey,
+    leader: Option<Pubkey>,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::migrate_nft_auction(
+                auction_info,
+                admin_authority.pubkey(),
+                leader,
+            ),
+        ],
+        Some(&admin_authority.pubkey()),
+        &[admin_authority],
+        blockhash,
+    )
+}
+
+/// Cancel the auction and refund the given bidders in the same transaction
+pub fn do_cancel_nft_auction(
+    admin_authority: &Keypair,
+    auction_info: Pubkey,
+    consignment: Option<Consignment>,
+    payment_mint: Option<Pubkey>,
+    bidders: &[Pubkey],
+    blockhash: Hash,
+) -> Transaction {
+    let mut instructions = vec![
+        voila_nft::instruction::cancel_nft_auction(
+            auction_info,
+            admin_authority.pubkey(),
+            consignment,
+        ),
+    ];
+    instructions.extend(bidders.iter().map(|bidder| {
+        voila_nft::instruction::claim_bid_refund(auction_info, *bidder, payment_mint)
+    }));
+
+    Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&admin_authority.pubkey()),
+        &[admin_authority],
+        blockhash,
+    )
+}
+
+pub fn do_create_nft_auction_collection(
+    admin_authority: &Keypair,
+    auction_info: Pubkey,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::create_nft_auction_collection(
+                auction_info,
+                admin_authority.pubkey(),
+            ),
+        ],
+        Some(&admin_authority.pubkey()),
+        &[admin_authority],
+        blockhash,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn do_create_common_nft(
     admin_authority: &Keypair,
     receipt: Pubkey,
     price: u64,
     max_amount: u16,
+    presale: Option<Presale>,
+    sale_start: Option<i64>,
+    sale_end: Option<i64>,
+    max_per_wallet: Option<u16>,
+    payment_mint: Option<Pubkey>,
+    allowlist: Option<&Allowlist>,
+    royalty: Option<Royalty>,
     name: String,
     uri: String,
     blockhash: Hash,
@@ -68,6 +198,13 @@ pub fn do_create_common_nft(
                 receipt,
                 price,
                 max_amount,
+                presale,
+                sale_start,
+                sale_end,
+                max_per_wallet,
+                payment_mint,
+                allowlist.map(Allowlist::root),
+                royalty,
                 name,
                 uri,
             ),
@@ -78,6 +215,48 @@ pub fn do_create_common_nft(
     )
 }
 
+pub fn do_create_common_nft_collection(
+    admin_authority: &Keypair,
+    nft_info: Pubkey,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::create_common_nft_collection(
+                nft_info,
+                admin_authority.pubkey(),
+            ),
+        ],
+        Some(&admin_authority.pubkey()),
+        &[admin_authority],
+        blockhash,
+    )
+}
+
+pub fn do_withdraw_from_nft_auction(
+    admin_authority: &Keypair,
+    auction_info: Pubkey,
+    receipt: Pubkey,
+    payment_mint: Option<Pubkey>,
+    seller: Option<Pubkey>,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::withdraw_from_nft_auction(
+                auction_info,
+                admin_authority.pubkey(),
+                receipt,
+                payment_mint,
+                seller,
+            ),
+        ],
+        Some(&admin_authority.pubkey()),
+        &[admin_authority],
+        blockhash,
+    )
+}
+
 pub fn do_purchase_key(
     user_authority: &Keypair,
     key_info: Pubkey,
@@ -98,15 +277,19 @@ pub fn do_purchase_key(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn do_purchase_common_nft(
     user_authority: &Keypair,
     nft_info: Pubkey,
     receipt: Pubkey,
     nft_id: u16,
+    presale_key_info: Option<Pubkey>,
+    payment_mint: Option<Pubkey>,
+    max_per_wallet: Option<u16>,
+    allowlist: Option<&Allowlist>,
+    collection: bool,
     blockhash: Hash,
 ) -> Transaction {
-    let (nft_mint, _, _, _) = get_common_nft_mint_pda(&nft_info, nft_id, &voila_nft::ID);
-
     Transaction::new_signed_with_payer(
         &[
             voila_nft::instruction::purchase_common_nft(
@@ -114,11 +297,16 @@ pub fn do_purchase_common_nft(
                 receipt,
                 user_authority.pubkey(),
                 nft_id,
+                presale_key_info,
+                payment_mint,
+                max_per_wallet,
+                allowlist.and_then(|allowlist| allowlist.proof(&user_authority.pubkey())),
             ),
             voila_nft::instruction::bind_common_nft_on_metaplex(
                 nft_info,
-                nft_mint,
+                nft_id,
                 user_authority.pubkey(),
+                collection,
             ),
         ],
         Some(&user_authority.pubkey()),
@@ -127,11 +315,57 @@ pub fn do_purchase_common_nft(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn do_purchase_common_nft_batch(
+    user_authority: &Keypair,
+    nft_info: Pubkey,
+    receipt: Pubkey,
+    first_nft_id: u16,
+    count: u8,
+    presale_key_info: Option<Pubkey>,
+    payment_mint: Option<Pubkey>,
+    max_per_wallet: Option<u16>,
+    allowlist: Option<&Allowlist>,
+    collection: bool,
+    blockhash: Hash,
+) -> Transaction {
+    let mut instructions = vec![
+        voila_nft::instruction::purchase_common_nft_batch(
+            nft_info,
+            receipt,
+            user_authority.pubkey(),
+            first_nft_id,
+            count,
+            presale_key_info,
+            payment_mint,
+            max_per_wallet,
+            allowlist.and_then(|allowlist| allowlist.proof(&user_authority.pubkey())),
+        ),
+    ];
+    for nft_id in first_nft_id..first_nft_id + count as u16 {
+        instructions.push(voila_nft::instruction::bind_common_nft_on_metaplex(
+            nft_info,
+            nft_id,
+            user_authority.pubkey(),
+            collection,
+        ));
+    }
+
+    Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&user_authority.pubkey()),
+        &[user_authority],
+        blockhash,
+    )
+}
+
 pub fn do_bid_in_nft_auction(
     user_authority: &Keypair,
     auction_info: Pubkey,
-    old_bidder: Option<Pubkey>,
+    bid_history_page: u32,
+    key_gate: Option<Pubkey>,
     price: u64,
+    payment_mint: Option<Pubkey>,
     blockhash: Hash,
 ) -> Transaction {
     Transaction::new_signed_with_payer(
@@ -139,12 +373,312 @@ pub fn do_bid_in_nft_auction(
             voila_nft::instruction::bid_in_nft_auction(
                 auction_info,
                 user_authority.pubkey(),
-                old_bidder,
+                bid_history_page,
+                key_gate,
+                price,
+                payment_mint,
+            ),
+        ],
+        Some(&user_authority.pubkey()),
+        &[user_authority],
+        blockhash,
+    )
+}
+
+pub fn do_bid_with_max_in_nft_auction(
+    user_authority: &Keypair,
+    auction_info: Pubkey,
+    bid_history_page: u32,
+    key_gate: Option<Pubkey>,
+    max_price: u64,
+    payment_mint: Option<Pubkey>,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::bid_with_max_in_nft_auction(
+                auction_info,
+                user_authority.pubkey(),
+                bid_history_page,
+                key_gate,
+                max_price,
+                payment_mint,
+            ),
+        ],
+        Some(&user_authority.pubkey()),
+        &[user_authority],
+        blockhash,
+    )
+}
+
+pub fn do_buy_now_in_nft_auction(
+    user_authority: &Keypair,
+    auction_info: Pubkey,
+    bid_history_page: u32,
+    key_gate: Option<Pubkey>,
+    payment_mint: Option<Pubkey>,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::buy_now_in_nft_auction(
+                auction_info,
+                user_authority.pubkey(),
+                bid_history_page,
+                key_gate,
+                payment_mint,
+            ),
+        ],
+        Some(&user_authority.pubkey()),
+        &[user_authority],
+        blockhash,
+    )
+}
+
+pub fn do_claim_bid_refund(
+    user_authority: &Keypair,
+    auction_info: Pubkey,
+    payment_mint: Option<Pubkey>,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::claim_bid_refund(
+                auction_info,
+                user_authority.pubkey(),
+                payment_mint,
+            ),
+        ],
+        Some(&user_authority.pubkey()),
+        &[user_authority],
+        blockhash,
+    )
+}
+
+pub fn do_settle_nft_auction(
+    cranker: &Keypair,
+    auction_info: Pubkey,
+    winner: Pubkey,
+    index: u8,
+    consigned_mint: Option<Pubkey>,
+    payment_mint: Option<Pubkey>,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::settle_nft_auction(
+                auction_info,
+                cranker.pubkey(),
+                winner,
+                index,
+                consigned_mint,
+                payment_mint,
+            ),
+        ],
+        Some(&cranker.pubkey()),
+        &[cranker],
+        blockhash,
+    )
+}
+
+pub fn do_reclaim_consigned_nft(
+    seller: &Keypair,
+    auction_info: Pubkey,
+    nft_mint: Pubkey,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::reclaim_consigned_nft(
+                auction_info,
+                seller.pubkey(),
+                nft_mint,
+            ),
+        ],
+        Some(&seller.pubkey()),
+        &[seller],
+        blockhash,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn do_create_dutch_auction(
+    admin_authority: &Keypair,
+    sn: u16,
+    receipt: Pubkey,
+    start_time: i64,
+    end_time: i64,
+    start_price: u64,
+    floor_price: u64,
+    price_decay: PriceDecay,
+    name: String,
+    uri: String,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::create_dutch_auction(
+                admin_authority.pubkey(),
+                sn,
+                receipt,
+                start_time,
+                end_time,
+                start_price,
+                floor_price,
+                price_decay,
+                name,
+                uri,
+            ),
+        ],
+        Some(&admin_authority.pubkey()),
+        &[admin_authority],
+        blockhash,
+    )
+}
+
+pub fn do_buy_in_dutch_auction(
+    user_authority: &Keypair,
+    dutch_auction: Pubkey,
+    receipt: Pubkey,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::buy_in_dutch_auction(
+                dutch_auction,
+                receipt,
+                user_authority.pubkey(),
+            ),
+        ],
+        Some(&user_authority.pubkey()),
+        &[user_authority],
+        blockhash,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn do_create_sealed_auction(
+    admin_authority: &Keypair,
+    sn: u16,
+    receipt: Pubkey,
+    kind: SealedAuctionKind,
+    start_time: i64,
+    commit_end_time: i64,
+    reveal_end_time: i64,
+    min_price: u64,
+    slash_bps: u16,
+    name: String,
+    uri: String,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::create_sealed_auction(
+                admin_authority.pubkey(),
+                sn,
+                receipt,
+                kind,
+                start_time,
+                commit_end_time,
+                reveal_end_time,
+                min_price,
+                slash_bps,
+                name,
+                uri,
+            ),
+        ],
+        Some(&admin_authority.pubkey()),
+        &[admin_authority],
+        blockhash,
+    )
+}
+
+pub fn do_commit_sealed_bid(
+    user_authority: &Keypair,
+    sealed_auction: Pubkey,
+    price: u64,
+    salt: [u8; 32],
+    deposit: u64,
+    blockhash: Hash,
+) -> Transaction {
+    let commitment = sealed_bid_commitment(price, &salt, &user_authority.pubkey());
+
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::commit_sealed_bid(
+                sealed_auction,
+                user_authority.pubkey(),
+                commitment,
+                deposit,
+            ),
+        ],
+        Some(&user_authority.pubkey()),
+        &[user_authority],
+        blockhash,
+    )
+}
+
+pub fn do_reveal_sealed_bid(
+    user_authority: &Keypair,
+    sealed_auction: Pubkey,
+    price: u64,
+    salt: [u8; 32],
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::reveal_sealed_bid(
+                sealed_auction,
+                user_authority.pubkey(),
                 price,
+                salt,
             ),
         ],
         Some(&user_authority.pubkey()),
         &[user_authority],
         blockhash,
     )
+}
+
+pub fn do_settle_sealed_auction(
+    cranker: &Keypair,
+    sealed_auction: Pubkey,
+    receipt: Pubkey,
+    winner: Pubkey,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::settle_sealed_auction(
+                sealed_auction,
+                receipt,
+                cranker.pubkey(),
+                winner,
+            ),
+        ],
+        Some(&cranker.pubkey()),
+        &[cranker],
+        blockhash,
+    )
+}
+
+pub fn do_refund_sealed_bid(
+    payer: &Keypair,
+    sealed_auction: Pubkey,
+    receipt: Pubkey,
+    bidder: Pubkey,
+    blockhash: Hash,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            voila_nft::instruction::refund_sealed_bid(
+                sealed_auction,
+                receipt,
+                bidder,
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[payer],
+        blockhash,
+    )
 }
\ No newline at end of file
@@ -42,6 +42,16 @@ pub enum VoilaError {
     NotRentExempt,
     #[error("Account is not initialized")]
     NotInitialized,
+    #[error("NFT auction reserve price is not met")]
+    ReserveNotMet,
+    #[error("NFT sale is not started")]
+    SaleNotStarted,
+    #[error("NFT sale is ended")]
+    SaleEnded,
+    #[error("NFT purchase exceeds the limit per wallet")]
+    WalletLimitExceeded,
+    #[error("Buyer is not in the allowlist")]
+    NotInAllowlist,
 }
 
 impl From<VoilaError> for ProgramError {
@@ -1,4 +1,5 @@
 use std::convert::TryInto;
+use borsh::{BorshSerialize, BorshDeserialize};
 use solana_program::{
     program_error::ProgramError,
     pubkey::{Pubkey, PUBKEY_BYTES},
@@ -9,24 +10,103 @@ use solana_program::{
 };
 use spl_associated_token_account::get_associated_token_address;
 
-use crate::{error::VoilaError, pda::*, ID};
+use crate::{
+    error::VoilaError,
+    nft::{
+        Presale,
+        Royalty,
+        allowlist::AllowlistProof,
+        auction::{BidExtension, ReservePrice, Consignment, NFTAuctionUpdate},
+        dutch_auction::PriceDecay,
+        sealed_auction::SealedAuctionKind,
+    },
+    pda::*,
+    ID,
+};
 
 #[derive(Debug, PartialEq)]
 pub enum VoilaInstruction {
     // 0 ~ 127 user
     PurchaseKey,
-    PurchaseCommonNFT,
+    PurchaseCommonNFT(Option<AllowlistProof>),
     #[cfg(feature = "metaplex")]
-    BindCommonNFTOnMetaplex,
+    BindCommonNFTOnMetaplex(u16),
     BidInNFTAuction(u64),
-    ClaimNFTFromAuction,
+    ClaimNFTFromAuction(u8),
+    #[cfg(feature = "metaplex")]
+    BindAuctionNFTOnMetaplex(u8),
+    ClaimBidRefund,
+    BuyNowInNFTAuction,
+    BuyInDutchAuction,
+    CommitSealedBid([u8; 32], u64),
+    RevealSealedBid(u64, [u8; 32]),
+    SettleSealedAuction,
+    RefundSealedBid,
+    ReclaimConsignedNFT,
+    SettleNFTAuction(u8),
+    BidWithMaxInNFTAuction(u64),
+    PurchaseCommonNFTBatch(u8, Option<AllowlistProof>),
     #[cfg(feature = "metaplex")]
-    BindAuctionNFTOnMetaplex,
+    BindDutchAuctionNFTOnMetaplex,
+    #[cfg(feature = "metaplex")]
+    BindSealedAuctionNFTOnMetaplex,
     // 128 ~ admin
     CreateKeyInfo(Pubkey, u64),
-    CreateCommonNFT(Pubkey, u64, u16, String, String),
-    CreateNFTAuction(u16, UnixTimestamp, UnixTimestamp, u64, u64, String, String),
+    CreateCommonNFT(
+        Pubkey,
+        u64,
+        u16,
+        Option<Presale>,
+        Option<UnixTimestamp>,
+        Option<UnixTimestamp>,
+        Option<u16>,
+        Option<Pubkey>,
+        Option<[u8; 32]>,
+        Option<Royalty>,
+        String,
+        String,
+    ),
+    CreateNFTAuction(
+        u16,
+        Pubkey,
+        UnixTimestamp,
+        UnixTimestamp,
+        u64,
+        u64,
+        u16,
+        u8,
+        Option<BidExtension>,
+        Option<ReservePrice>,
+        Option<u64>,
+        Option<Pubkey>,
+        Option<Consignment>,
+        Option<u64>,
+        Option<Pubkey>,
+        Option<Royalty>,
+        String,
+        String,
+    ),
     WithdrawFromNFTAuction,
+    CreateDutchAuction(u16, Pubkey, UnixTimestamp, UnixTimestamp, u64, u64, PriceDecay, String, String),
+    CreateSealedAuction(
+        u16,
+        Pubkey,
+        SealedAuctionKind,
+        UnixTimestamp,
+        UnixTimestamp,
+        UnixTimestamp,
+        u64,
+        u16,
+        String,
+        String,
+    ),
+    UpdateNFTAuction(NFTAuctionUpdate),
+    CancelNFTAuction,
+    #[cfg(feature = "metaplex")]
+    CreateCommonNFTCollection,
+    #[cfg(feature = "metaplex")]
+    CreateNFTAuctionCollection,
+    MigrateNFTAuction,
 }
 
 impl VoilaInstruction {
@@ -36,16 +116,61 @@ impl VoilaInstruction {
             .ok_or(VoilaError::InstructionUnpackError)?;
         Ok(match tag {
             0 => Self::PurchaseKey,
-            1 => Self::PurchaseCommonNFT,
+            1 => {
+                let (proof, _rest) = Self::unpack_borsh(rest)?;
+                Self::PurchaseCommonNFT(proof)
+            }
             #[cfg(feature = "metaplex")]
-            2 => Self::BindCommonNFTOnMetaplex,
+            2 => {
+                let (nft_id, _rest) = Self::unpack_u16(rest)?;
+                Self::BindCommonNFTOnMetaplex(nft_id)
+            }
             3 => {
                 let (raise_price, _rest) = Self::unpack_u64(rest)?;
                 Self::BidInNFTAuction(raise_price)
             }
-            4 => Self::ClaimNFTFromAuction,
+            4 => {
+                let (index, _rest) = Self::unpack_u8(rest)?;
+                Self::ClaimNFTFromAuction(index)
+            }
+            #[cfg(feature = "metaplex")]
+            5 => {
+                let (index, _rest) = Self::unpack_u8(rest)?;
+                Self::BindAuctionNFTOnMetaplex(index)
+            }
+            6 => Self::ClaimBidRefund,
+            7 => Self::BuyNowInNFTAuction,
+            8 => Self::BuyInDutchAuction,
+            9 => {
+                let (commitment, rest) = Self::unpack_bytes32(rest)?;
+                let (deposit, _rest) = Self::unpack_u64(rest)?;
+                Self::CommitSealedBid(commitment, deposit)
+            }
+            10 => {
+                let (price, rest) = Self::unpack_u64(rest)?;
+                let (salt, _rest) = Self::unpack_bytes32(rest)?;
+                Self::RevealSealedBid(price, salt)
+            }
+            11 => Self::SettleSealedAuction,
+            12 => Self::RefundSealedBid,
+            13 => Self::ReclaimConsignedNFT,
+            14 => {
+                let (index, _rest) = Self::unpack_u8(rest)?;
+                Self::SettleNFTAuction(index)
+            }
+            15 => {
+                let (max_price, _rest) = Self::unpack_u64(rest)?;
+                Self::BidWithMaxInNFTAuction(max_price)
+            }
+            16 => {
+                let (count, rest) = Self::unpack_u8(rest)?;
+                let (proof, _rest) = Self::unpack_borsh(rest)?;
+                Self::PurchaseCommonNFTBatch(count, proof)
+            }
             #[cfg(feature = "metaplex")]
-            5 => Self::BindAuctionNFTOnMetaplex,
+            17 => Self::BindDutchAuctionNFTOnMetaplex,
+            #[cfg(feature = "metaplex")]
+            18 => Self::BindSealedAuctionNFTOnMetaplex,
             128 => {
                 let (receipt, rest) = Self::unpack_pubkey(rest)?;
                 let (price, _rest) = Self::unpack_u64(rest)?;
@@ -55,21 +180,127 @@ impl VoilaInstruction {
                 let (receipt, rest) = Self::unpack_pubkey(rest)?;
                 let (price, rest) = Self::unpack_u64(rest)?;
                 let (max_amount, rest) = Self::unpack_u16(rest)?;
+                let (presale, rest) = Self::unpack_borsh(rest)?;
+                let (sale_start, rest) = Self::unpack_borsh(rest)?;
+                let (sale_end, rest) = Self::unpack_borsh(rest)?;
+                let (max_per_wallet, rest) = Self::unpack_borsh(rest)?;
+                let (payment_mint, rest) = Self::unpack_borsh(rest)?;
+                let (allowlist_root, rest) = Self::unpack_borsh(rest)?;
+                let (royalty, rest) = Self::unpack_borsh(rest)?;
                 let (name, rest) = Self::unpack_string(rest)?;
                 let (uri, _rest) = Self::unpack_string(rest)?;
-                Self::CreateCommonNFT(receipt, price, max_amount, name, uri)
+                Self::CreateCommonNFT(
+                    receipt,
+                    price,
+                    max_amount,
+                    presale,
+                    sale_start,
+                    sale_end,
+                    max_per_wallet,
+                    payment_mint,
+                    allowlist_root,
+                    royalty,
+                    name,
+                    uri,
+                )
             }
             130 => {
                 let (sn, rest) = Self::unpack_u16(rest)?;
+                let (receipt, rest) = Self::unpack_pubkey(rest)?;
                 let (start_time, rest) = Self::unpack_i64(rest)?;
                 let (end_time, rest) = Self::unpack_i64(rest)?;
                 let (base_price, rest) = Self::unpack_u64(rest)?;
                 let (min_raise_price, rest) = Self::unpack_u64(rest)?;
+                let (min_raise_bps, rest) = Self::unpack_u16(rest)?;
+                let (winners, rest) = Self::unpack_u8(rest)?;
+                let (bid_extension, rest) = Self::unpack_borsh(rest)?;
+                let (reserve_price, rest) = Self::unpack_borsh(rest)?;
+                let (buy_now_price, rest) = Self::unpack_borsh(rest)?;
+                let (payment_mint, rest) = Self::unpack_borsh(rest)?;
+                let (consignment, rest) = Self::unpack_borsh(rest)?;
+                let (crank_reward, rest) = Self::unpack_borsh(rest)?;
+                let (key_gate, rest) = Self::unpack_borsh(rest)?;
+                let (royalty, rest) = Self::unpack_borsh(rest)?;
                 let (name, rest) = Self::unpack_string(rest)?;
                 let (uri, _rest) = Self::unpack_string(rest)?;
-                Self::CreateNFTAuction(sn, start_time, end_time, base_price, min_raise_price, name, uri)
+                Self::CreateNFTAuction(
+                    sn,
+                    receipt,
+                    start_time,
+                    end_time,
+                    base_price,
+                    min_raise_price,
+                    min_raise_bps,
+                    winners,
+                    bid_extension,
+                    reserve_price,
+                    buy_now_price,
+                    payment_mint,
+                    consignment,
+                    crank_reward,
+                    key_gate,
+                    royalty,
+                    name,
+                    uri,
+                )
             }
             131 => Self::WithdrawFromNFTAuction,
+            132 => {
+                let (sn, rest) = Self::unpack_u16(rest)?;
+                let (receipt, rest) = Self::unpack_pubkey(rest)?;
+                let (start_time, rest) = Self::unpack_i64(rest)?;
+                let (end_time, rest) = Self::unpack_i64(rest)?;
+                let (start_price, rest) = Self::unpack_u64(rest)?;
+                let (floor_price, rest) = Self::unpack_u64(rest)?;
+                let (price_decay, rest) = Self::unpack_borsh(rest)?;
+                let (name, rest) = Self::unpack_string(rest)?;
+                let (uri, _rest) = Self::unpack_string(rest)?;
+                Self::CreateDutchAuction(
+                    sn,
+                    receipt,
+                    start_time,
+                    end_time,
+                    start_price,
+                    floor_price,
+                    price_decay,
+                    name,
+                    uri,
+                )
+            }
+            133 => {
+                let (sn, rest) = Self::unpack_u16(rest)?;
+                let (receipt, rest) = Self::unpack_pubkey(rest)?;
+                let (kind, rest) = Self::unpack_borsh(rest)?;
+                let (start_time, rest) = Self::unpack_i64(rest)?;
+                let (commit_end_time, rest) = Self::unpack_i64(rest)?;
+                let (reveal_end_time, rest) = Self::unpack_i64(rest)?;
+                let (min_price, rest) = Self::unpack_u64(rest)?;
+                let (slash_bps, rest) = Self::unpack_u16(rest)?;
+                let (name, rest) = Self::unpack_string(rest)?;
+                let (uri, _rest) = Self::unpack_string(rest)?;
+                Self::CreateSealedAuction(
+                    sn,
+                    receipt,
+                    kind,
+                    start_time,
+                    commit_end_time,
+                    reveal_end_time,
+                    min_price,
+                    slash_bps,
+                    name,
+                    uri,
+                )
+            }
+            134 => {
+                let (update, _rest) = Self::unpack_borsh(rest)?;
+                Self::UpdateNFTAuction(update)
+            }
+            135 => Self::CancelNFTAuction,
+            #[cfg(feature = "metaplex")]
+            136 => Self::CreateCommonNFTCollection,
+            #[cfg(feature = "metaplex")]
+            137 => Self::CreateNFTAuctionCollection,
+            138 => Self::MigrateNFTAuction,
             _ => return Err(VoilaError::InstructionUnpackError.into()),
         })
     }
@@ -78,26 +309,91 @@ impl VoilaInstruction {
         let mut buf = Vec::new();
         match self {
             Self::PurchaseKey => buf.push(0),
-            Self::PurchaseCommonNFT => buf.push(1),
+            Self::PurchaseCommonNFT(proof) => {
+                buf.push(1);
+                Self::pack_borsh(&proof, &mut buf);
+            }
             #[cfg(feature = "metaplex")]
-            Self::BindCommonNFTOnMetaplex => buf.push(2),
+            Self::BindCommonNFTOnMetaplex(nft_id) => {
+                buf.push(2);
+                buf.extend_from_slice(&nft_id.to_le_bytes());
+            }
             Self::BidInNFTAuction(raise_price) => {
                 buf.push(3);
                 buf.extend(raise_price.to_le_bytes());
             }
-            Self::ClaimNFTFromAuction => buf.push(4),
+            Self::ClaimNFTFromAuction(index) => {
+                buf.push(4);
+                buf.push(index);
+            }
+            #[cfg(feature = "metaplex")]
+            Self::BindAuctionNFTOnMetaplex(index) => {
+                buf.push(5);
+                buf.push(index);
+            }
+            Self::ClaimBidRefund => buf.push(6),
+            Self::BuyNowInNFTAuction => buf.push(7),
+            Self::BuyInDutchAuction => buf.push(8),
+            Self::CommitSealedBid(commitment, deposit) => {
+                buf.push(9);
+                buf.extend_from_slice(&commitment);
+                buf.extend_from_slice(&deposit.to_le_bytes());
+            }
+            Self::RevealSealedBid(price, salt) => {
+                buf.push(10);
+                buf.extend_from_slice(&price.to_le_bytes());
+                buf.extend_from_slice(&salt);
+            }
+            Self::SettleSealedAuction => buf.push(11),
+            Self::RefundSealedBid => buf.push(12),
+            Self::ReclaimConsignedNFT => buf.push(13),
+            Self::SettleNFTAuction(index) => {
+                buf.push(14);
+                buf.push(index);
+            }
+            Self::BidWithMaxInNFTAuction(max_price) => {
+                buf.push(15);
+                buf.extend(max_price.to_le_bytes());
+            }
+            Self::PurchaseCommonNFTBatch(count, proof) => {
+                buf.push(16);
+                buf.push(count);
+                Self::pack_borsh(&proof, &mut buf);
+            }
+            #[cfg(feature = "metaplex")]
+            Self::BindDutchAuctionNFTOnMetaplex => buf.push(17),
             #[cfg(feature = "metaplex")]
-            Self::BindAuctionNFTOnMetaplex => buf.push(5),
+            Self::BindSealedAuctionNFTOnMetaplex => buf.push(18),
             Self::CreateKeyInfo(receipt, price) => {
                 buf.push(128);
                 buf.extend_from_slice(&receipt.as_ref());
                 buf.extend_from_slice(&price.to_le_bytes());
             }
-            Self::CreateCommonNFT(receipt, price, max_amount, name, uri) => {
+            Self::CreateCommonNFT(
+                receipt,
+                price,
+                max_amount,
+                presale,
+                sale_start,
+                sale_end,
+                max_per_wallet,
+                payment_mint,
+                allowlist_root,
+                royalty,
+                name,
+                uri,
+            ) => {
                 buf.push(129);
                 buf.extend_from_slice(&receipt.as_ref());
                 buf.extend_from_slice(&price.to_le_bytes());
                 buf.extend_from_slice(&max_amount.to_le_bytes());
+                Self::pack_borsh(&presale, &mut buf);
+                Self::pack_borsh(&sale_start, &mut buf);
+                Self::pack_borsh(&sale_end, &mut buf);
+                Self::pack_borsh(&max_per_wallet, &mut buf);
+                Self::pack_borsh(&payment_mint, &mut buf);
+                Self::pack_borsh(&allowlist_root, &mut buf);
+                Self::pack_borsh(&royalty, &mut buf);
 
                 let name_data = name.as_bytes();
                 buf.push(name_data.len() as u8);
@@ -109,19 +405,41 @@ impl VoilaInstruction {
             }
             Self::CreateNFTAuction(
                 sn,
+                receipt,
                 start_time,
                 end_time,
                 base_price,
                 min_raise_price,
+                min_raise_bps,
+                winners,
+                bid_extension,
+                reserve_price,
+                buy_now_price,
+                payment_mint,
+                consignment,
+                crank_reward,
+                key_gate,
+                royalty,
                 name,
                 uri,
             ) => {
                 buf.push(130);
                 buf.extend_from_slice(&sn.to_le_bytes());
+                buf.extend_from_slice(receipt.as_ref());
                 buf.extend_from_slice(&start_time.to_le_bytes());
                 buf.extend_from_slice(&end_time.to_le_bytes());
                 buf.extend_from_slice(&base_price.to_le_bytes());
                 buf.extend_from_slice(&min_raise_price.to_le_bytes());
+                buf.extend_from_slice(&min_raise_bps.to_le_bytes());
+                buf.push(winners);
+                Self::pack_borsh(&bid_extension, &mut buf);
+                Self::pack_borsh(&reserve_price, &mut buf);
+                Self::pack_borsh(&buy_now_price, &mut buf);
+                Self::pack_borsh(&payment_mint, &mut buf);
+                Self::pack_borsh(&consignment, &mut buf);
+                Self::pack_borsh(&crank_reward, &mut buf);
+                Self::pack_borsh(&key_gate, &mut buf);
+                Self::pack_borsh(&royalty, &mut buf);
 
                 let name_data = name.as_bytes();
                 buf.push(name_data.len() as u8);
@@ -132,11 +450,92 @@ impl VoilaInstruction {
                 buf.extend_from_slice(uri_data);
             }
             Self::WithdrawFromNFTAuction => buf.push(131),
+            Self::CreateDutchAuction(
+                sn,
+                receipt,
+                start_time,
+                end_time,
+                start_price,
+                floor_price,
+                price_decay,
+                name,
+                uri,
+            ) => {
+                buf.push(132);
+                buf.extend_from_slice(&sn.to_le_bytes());
+                buf.extend_from_slice(receipt.as_ref());
+                buf.extend_from_slice(&start_time.to_le_bytes());
+                buf.extend_from_slice(&end_time.to_le_bytes());
+                buf.extend_from_slice(&start_price.to_le_bytes());
+                buf.extend_from_slice(&floor_price.to_le_bytes());
+                Self::pack_borsh(&price_decay, &mut buf);
+
+                let name_data = name.as_bytes();
+                buf.push(name_data.len() as u8);
+                buf.extend_from_slice(name_data);
+
+                let uri_data = uri.as_bytes();
+                buf.push(uri_data.len() as u8);
+                buf.extend_from_slice(uri_data);
+            }
+            Self::CreateSealedAuction(
+                sn,
+                receipt,
+                kind,
+                start_time,
+                commit_end_time,
+                reveal_end_time,
+                min_price,
+                slash_bps,
+                name,
+                uri,
+            ) => {
+                buf.push(133);
+                buf.extend_from_slice(&sn.to_le_bytes());
+                buf.extend_from_slice(receipt.as_ref());
+                Self::pack_borsh(&kind, &mut buf);
+                buf.extend_from_slice(&start_time.to_le_bytes());
+                buf.extend_from_slice(&commit_end_time.to_le_bytes());
+                buf.extend_from_slice(&reveal_end_time.to_le_bytes());
+                buf.extend_from_slice(&min_price.to_le_bytes());
+                buf.extend_from_slice(&slash_bps.to_le_bytes());
+
+                let name_data = name.as_bytes();
+                buf.push(name_data.len() as u8);
+                buf.extend_from_slice(name_data);
+
+                let uri_data = uri.as_bytes();
+                buf.push(uri_data.len() as u8);
+                buf.extend_from_slice(uri_data);
+            }
+            Self::UpdateNFTAuction(update) => {
+                buf.push(134);
+                Self::pack_borsh(&update, &mut buf);
+            }
+            Self::CancelNFTAuction => buf.push(135),
+            #[cfg(feature = "metaplex")]
+            Self::CreateCommonNFTCollection => buf.push(136),
+            #[cfg(feature = "metaplex")]
+            Self::CreateNFTAuctionCollection => buf.push(137),
+            Self::MigrateNFTAuction => buf.push(138),
         }
 
         buf
     }
     
+    fn pack_borsh<T: BorshSerialize>(value: &T, buf: &mut Vec<u8>) {
+        value.serialize(buf).expect("serialize into vec");
+    }
+
+    fn unpack_borsh<T: BorshDeserialize>(input: &[u8]) -> Result<(T, &[u8]), ProgramError> {
+        let mut rest = input;
+        let value = T::deserialize(&mut rest).map_err(|_| {
+            msg!("Borsh data cannot be unpacked");
+            VoilaError::InstructionUnpackError
+        })?;
+        Ok((value, rest))
+    }
+
     fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
         if input.len() < PUBKEY_BYTES {
             msg!("Pubkey cannot be unpacked");
@@ -147,6 +546,18 @@ impl VoilaInstruction {
         Ok((pk, rest))
     }
 
+    fn unpack_bytes32(input: &[u8]) -> Result<([u8; 32], &[u8]), ProgramError> {
+        if input.len() < 32 {
+            msg!("32 bytes cannot be unpacked");
+            return Err(VoilaError::InstructionUnpackError.into());
+        }
+        let (bytes, rest) = input.split_at(32);
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| VoilaError::InstructionUnpackError)?;
+        Ok((bytes, rest))
+    }
+
     fn unpack_string(input: &[u8]) -> Result<(String, &[u8]), ProgramError> {
         let (len, rest) = input.split_first().ok_or_else(|| {
             msg!("String cannot be unpacked");
@@ -187,6 +598,14 @@ impl VoilaInstruction {
         Ok((amount, rest))
     }
 
+    fn unpack_u8(input: &[u8]) -> Result<(u8, &[u8]), ProgramError> {
+        let (&amount, rest) = input.split_first().ok_or_else(|| {
+            msg!("u8 cannot be unpacked");
+            VoilaError::InstructionUnpackError
+        })?;
+        Ok((amount, rest))
+    }
+
     fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
         if input.len() < 2 {
             msg!("u16 cannot be unpacked");
@@ -249,6 +668,13 @@ pub fn create_common_nft(
     receipt: Pubkey,
     price: u64,
     max_amount: u16,
+    presale: Option<Presale>,
+    sale_start: Option<UnixTimestamp>,
+    sale_end: Option<UnixTimestamp>,
+    max_per_wallet: Option<u16>,
+    payment_mint: Option<Pubkey>,
+    allowlist_root: Option<[u8; 32]>,
+    royalty: Option<Royalty>,
     name: String,
     uri: String,
 ) -> Instruction {
@@ -262,49 +688,179 @@ pub fn create_common_nft(
             AccountMeta::new(nft_info, false),
             AccountMeta::new(admin_authority, true),
         ],
-        data: VoilaInstruction::CreateCommonNFT(receipt, price, max_amount, name, uri).pack(),
+        data: VoilaInstruction::CreateCommonNFT(
+            receipt,
+            price,
+            max_amount,
+            presale,
+            sale_start,
+            sale_end,
+            max_per_wallet,
+            payment_mint,
+            allowlist_root,
+            royalty,
+            name,
+            uri,
+        ).pack(),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn purchase_common_nft(
     nft_info: Pubkey,
     receipt: Pubkey,
     user_authority: Pubkey,
     nft_id: u16,
+    presale_key_info: Option<Pubkey>,
+    payment_mint: Option<Pubkey>,
+    max_per_wallet: Option<u16>,
+    proof: Option<AllowlistProof>,
 ) -> Instruction {
     let (nft_authority, _, _) = get_common_nft_authority_pda(&nft_info, &ID);
     let (nft_mint, _, _, _) = get_common_nft_mint_pda(&nft_info, nft_id, &ID);
     let nft_account = get_associated_token_address(&user_authority, &nft_mint);
+    let receipt = payment_mint.map_or(receipt, |payment_mint| get_associated_token_address(&receipt, &payment_mint));
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(sysvar::clock::ID, false),
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        AccountMeta::new(nft_info, false),
+        AccountMeta::new_readonly(nft_authority, false),
+        AccountMeta::new(nft_mint, false),
+        AccountMeta::new(nft_account, false),
+        AccountMeta::new(receipt, false),
+        AccountMeta::new(user_authority, true),
+    ];
+    if let Some(key_info) = presale_key_info {
+        let (user_key_record, _, _, _) = get_user_key_record_pda(&key_info, &user_authority, &ID);
+        accounts.push(AccountMeta::new_readonly(user_key_record, false));
+    }
+    if max_per_wallet.is_some() || proof.as_ref().is_some_and(|proof| proof.allowance > 0) {
+        let (purchase_record, _, _, _, _) = get_purchase_record_pda(&nft_info, &user_authority, &ID);
+        accounts.push(AccountMeta::new(purchase_record, false));
+    }
+    if let Some(payment_mint) = payment_mint {
+        accounts.push(AccountMeta::new(get_associated_token_address(&user_authority, &payment_mint), false));
+    }
 
     Instruction {
         program_id: ID,
-        accounts: vec![
-            AccountMeta::new_readonly(sysvar::rent::ID, false),
-            AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
-            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
-            AccountMeta::new(nft_info, false),
-            AccountMeta::new_readonly(nft_authority, false),
+        accounts,
+        data: VoilaInstruction::PurchaseCommonNFT(proof).pack(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn purchase_common_nft_batch(
+    nft_info: Pubkey,
+    receipt: Pubkey,
+    user_authority: Pubkey,
+    first_nft_id: u16,
+    count: u8,
+    presale_key_info: Option<Pubkey>,
+    payment_mint: Option<Pubkey>,
+    max_per_wallet: Option<u16>,
+    proof: Option<AllowlistProof>,
+) -> Instruction {
+    let (nft_authority, _, _) = get_common_nft_authority_pda(&nft_info, &ID);
+    let receipt = payment_mint.map_or(receipt, |payment_mint| get_associated_token_address(&receipt, &payment_mint));
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(sysvar::clock::ID, false),
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        AccountMeta::new(nft_info, false),
+        AccountMeta::new_readonly(nft_authority, false),
+        AccountMeta::new(receipt, false),
+        AccountMeta::new(user_authority, true),
+    ];
+    if let Some(key_info) = presale_key_info {
+        let (user_key_record, _, _, _) = get_user_key_record_pda(&key_info, &user_authority, &ID);
+        accounts.push(AccountMeta::new_readonly(user_key_record, false));
+    }
+    if max_per_wallet.is_some() || proof.as_ref().is_some_and(|proof| proof.allowance > 0) {
+        let (purchase_record, _, _, _, _) = get_purchase_record_pda(&nft_info, &user_authority, &ID);
+        accounts.push(AccountMeta::new(purchase_record, false));
+    }
+    if let Some(payment_mint) = payment_mint {
+        accounts.push(AccountMeta::new(get_associated_token_address(&user_authority, &payment_mint), false));
+    }
+    for nft_id in first_nft_id..first_nft_id + count as u16 {
+        let (nft_mint, _, _, _) = get_common_nft_mint_pda(&nft_info, nft_id, &ID);
+        let nft_account = get_associated_token_address(&user_authority, &nft_mint);
+
+        accounts.extend([
             AccountMeta::new(nft_mint, false),
             AccountMeta::new(nft_account, false),
-            AccountMeta::new(receipt, false),
-            AccountMeta::new(user_authority, true),
-        ],
-        data: VoilaInstruction::PurchaseCommonNFT.pack(),
+        ]);
+    }
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: VoilaInstruction::PurchaseCommonNFTBatch(count, proof).pack(),
     }
 }
 
 #[cfg(feature = "metaplex")]
 pub fn bind_common_nft_on_metaplex(
     nft_info: Pubkey,
-    nft_mint: Pubkey,
+    nft_id: u16,
     user_authority: Pubkey,
+    collection: bool,
+) -> Instruction {
+    use crate::nft::metaplex::{get_metaplex_metadata_account, get_metaplex_master_edition};
+
+    let (nft_authority, _, _) = get_common_nft_authority_pda(&nft_info, &ID);
+    let (nft_mint, _, _, _) = get_common_nft_mint_pda(&nft_info, nft_id, &ID);
+    let metadata = get_metaplex_metadata_account(&mpl_token_metadata::ID, &nft_mint);
+    let master_edition = get_metaplex_master_edition(&mpl_token_metadata::ID, &nft_mint);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+        AccountMeta::new_readonly(nft_info, false),
+        AccountMeta::new(nft_authority, false),
+        AccountMeta::new(nft_mint, false),
+        AccountMeta::new(metadata, false),
+        AccountMeta::new(master_edition, false),
+        AccountMeta::new(user_authority, true),
+    ];
+    if collection {
+        let (collection_mint, _, _, _) = get_collection_mint_pda(&nft_authority, &ID);
+        accounts.extend([
+            AccountMeta::new_readonly(collection_mint, false),
+            AccountMeta::new_readonly(get_metaplex_metadata_account(&mpl_token_metadata::ID, &collection_mint), false),
+            AccountMeta::new_readonly(get_metaplex_master_edition(&mpl_token_metadata::ID, &collection_mint), false),
+        ]);
+    }
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: VoilaInstruction::BindCommonNFTOnMetaplex(nft_id).pack(),
+    }
+}
+
+#[cfg(feature = "metaplex")]
+pub fn create_common_nft_collection(
+    nft_info: Pubkey,
+    admin: Pubkey,
 ) -> Instruction {
     use crate::nft::metaplex::{get_metaplex_metadata_account, get_metaplex_master_edition};
 
     let (nft_authority, _, _) = get_common_nft_authority_pda(&nft_info, &ID);
-    let metadata = get_metaplex_metadata_account(&metaplex_token_metadata::ID, &nft_mint);
-    let master_edition = get_metaplex_master_edition(&metaplex_token_metadata::ID, &nft_mint);
+    let (collection_mint, _, _, _) = get_collection_mint_pda(&nft_authority, &ID);
+    let collection_account = get_associated_token_address(&nft_authority, &collection_mint);
+    let metadata = get_metaplex_metadata_account(&mpl_token_metadata::ID, &collection_mint);
+    let master_edition = get_metaplex_master_edition(&mpl_token_metadata::ID, &collection_mint);
 
     Instruction {
         program_id: ID,
@@ -312,15 +868,17 @@ pub fn bind_common_nft_on_metaplex(
             AccountMeta::new_readonly(sysvar::rent::ID, false),
             AccountMeta::new_readonly(system_program::ID, false),
             AccountMeta::new_readonly(spl_token::ID, false),
-            AccountMeta::new_readonly(metaplex_token_metadata::ID, false),
-            AccountMeta::new_readonly(nft_info, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+            AccountMeta::new(nft_info, false),
             AccountMeta::new_readonly(nft_authority, false),
-            AccountMeta::new(nft_mint, false),
+            AccountMeta::new(collection_mint, false),
+            AccountMeta::new(collection_account, false),
             AccountMeta::new(metadata, false),
             AccountMeta::new(master_edition, false),
-            AccountMeta::new(user_authority, true),
+            AccountMeta::new(admin, true),
         ],
-        data: VoilaInstruction::BindCommonNFTOnMetaplex.pack(),
+        data: VoilaInstruction::CreateCommonNFTCollection.pack(),
     }
 }
 
@@ -328,134 +886,757 @@ pub fn bind_common_nft_on_metaplex(
 pub fn create_nft_auction(
     admin_authority: Pubkey,
     sn: u16,
+    receipt: Pubkey,
     start_time: UnixTimestamp,
     end_time: UnixTimestamp,
     base_price: u64,
     min_raise_price: u64,
+    min_raise_bps: u16,
+    winners: u8,
+    bid_extension: Option<BidExtension>,
+    reserve_price: Option<ReservePrice>,
+    buy_now_price: Option<u64>,
+    payment_mint: Option<Pubkey>,
+    consignment: Option<Consignment>,
+    crank_reward: Option<u64>,
+    key_gate: Option<Pubkey>,
+    royalty: Option<Royalty>,
     name: String,
     uri: String,
 ) -> Instruction {
     let (nft_auction, _, _, _, _)
     = get_nft_auction_pda(&admin_authority, sn, &ID);
 
+    let mut accounts = vec![
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(nft_auction, false),
+        AccountMeta::new(admin_authority, true),
+    ];
+    if let Some(payment_mint) = payment_mint {
+        let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
+        let escrow = get_associated_token_address(&nft_auction_authority, &payment_mint);
+
+        accounts.extend([
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(nft_auction_authority, false),
+            AccountMeta::new_readonly(payment_mint, false),
+            AccountMeta::new(escrow, false),
+        ]);
+    }
+    if let Some(consignment) = &consignment {
+        let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
+        let seller_nft_account = get_associated_token_address(&consignment.seller, &consignment.mint);
+        let nft_escrow = get_associated_token_address(&nft_auction_authority, &consignment.mint);
+
+        accounts.extend([
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(nft_auction_authority, false),
+            AccountMeta::new_readonly(consignment.mint, false),
+            AccountMeta::new_readonly(consignment.seller, true),
+            AccountMeta::new(seller_nft_account, false),
+            AccountMeta::new(nft_escrow, false),
+        ]);
+    }
+
     Instruction {
         program_id: ID,
-        accounts: vec![
-            AccountMeta::new_readonly(sysvar::rent::ID, false),
-            AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new(nft_auction, false),
-            AccountMeta::new(admin_authority, true),
-        ],
+        accounts,
         data: VoilaInstruction::CreateNFTAuction(
             sn,
+            receipt,
             start_time,
             end_time,
             base_price,
             min_raise_price,
+            min_raise_bps,
+            winners,
+            bid_extension,
+            reserve_price,
+            buy_now_price,
+            payment_mint,
+            consignment,
+            crank_reward,
+            key_gate,
+            royalty,
             name,
             uri,
         ).pack(),
     }
 }
 
-pub fn withdraw_from_nft_auction(
+pub fn update_nft_auction(
     nft_auction: Pubkey,
     admin: Pubkey,
-    receipt: Pubkey,
+    update: NFTAuctionUpdate,
 ) -> Instruction {
-    let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
-
     Instruction {
         program_id: ID,
         accounts: vec![
-            AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(nft_auction, false),
-            AccountMeta::new(nft_auction_authority, false),
+            AccountMeta::new(nft_auction, false),
             AccountMeta::new_readonly(admin, true),
-            AccountMeta::new(receipt, false),
         ],
-        data: VoilaInstruction::WithdrawFromNFTAuction.pack(),
+        data: VoilaInstruction::UpdateNFTAuction(update).pack(),
     }
 }
 
-pub fn bid_in_nft_auction(
+pub fn cancel_nft_auction(
     nft_auction: Pubkey,
-    new_bidder: Pubkey,
-    old_bidder: Option<Pubkey>,
-    raise_price: u64,
+    admin: Pubkey,
+    consignment: Option<Consignment>,
 ) -> Instruction {
     let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
 
     let mut accounts = vec![
         AccountMeta::new_readonly(sysvar::clock::ID, false),
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
         AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
         AccountMeta::new(nft_auction, false),
-        AccountMeta::new(nft_auction_authority, false),
-        AccountMeta::new(new_bidder, true),
+        AccountMeta::new_readonly(nft_auction_authority, false),
+        AccountMeta::new(admin, true),
     ];
-    if let Some(old_bidder) = old_bidder {
-        accounts.push(AccountMeta::new(old_bidder, false));
+    if let Some(consignment) = consignment {
+        accounts.extend([
+            AccountMeta::new_readonly(consignment.seller, false),
+            AccountMeta::new_readonly(consignment.mint, false),
+            AccountMeta::new(get_associated_token_address(&consignment.seller, &consignment.mint), false),
+            AccountMeta::new(get_associated_token_address(&nft_auction_authority, &consignment.mint), false),
+        ]);
     }
 
     Instruction {
         program_id: ID,
         accounts,
-        data: VoilaInstruction::BidInNFTAuction(raise_price).pack(),
+        data: VoilaInstruction::CancelNFTAuction.pack(),
     }
 }
 
-pub fn claim_from_nft_auction(
+fn escrow_account_metas(nft_auction_authority: &Pubkey, payment_mint: &Pubkey) -> [AccountMeta; 2] {
+    [
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new(get_associated_token_address(nft_auction_authority, payment_mint), false),
+    ]
+}
+
+/// `leader` is the current bidder of a legacy auction whose bid is still escrowed
+pub fn migrate_nft_auction(
     nft_auction: Pubkey,
-    owner: Pubkey,
+    admin: Pubkey,
+    leader: Option<Pubkey>,
 ) -> Instruction {
     let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
-    let (nft_mint, _, _) = get_auction_nft_mint_pda(&nft_auction_authority, &ID);
-    let nft_account = get_associated_token_address(&owner, &nft_mint);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(sysvar::clock::ID, false),
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(nft_auction, false),
+        AccountMeta::new_readonly(nft_auction_authority, false),
+        AccountMeta::new(admin, true),
+    ];
+    if let Some(leader) = leader {
+        let (bid_escrow, _, _, _, _) = get_bid_escrow_pda(&nft_auction, &leader, &ID);
+        accounts.push(AccountMeta::new(bid_escrow, false));
+    }
 
     Instruction {
         program_id: ID,
-        accounts: vec![
-            AccountMeta::new_readonly(sysvar::clock::ID, false),
-            AccountMeta::new_readonly(sysvar::rent::ID, false),
-            AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
-            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
-            AccountMeta::new(nft_auction, false),
-            AccountMeta::new_readonly(nft_auction_authority, false),
-            AccountMeta::new(owner, true),
-            AccountMeta::new(nft_mint, false),
-            AccountMeta::new(nft_account, false),
-        ],
-        data: VoilaInstruction::ClaimNFTFromAuction.pack(),
+        accounts,
+        data: VoilaInstruction::MigrateNFTAuction.pack(),
     }
 }
 
-#[cfg(feature = "metaplex")]
-pub fn bind_auction_nft_on_metaplex(
+pub fn withdraw_from_nft_auction(
     nft_auction: Pubkey,
-    nft_mint: Pubkey,
-    user_authority: Pubkey,
+    admin: Pubkey,
+    receipt: Pubkey,
+    payment_mint: Option<Pubkey>,
+    seller: Option<Pubkey>,
 ) -> Instruction {
-    use crate::nft::metaplex::{get_metaplex_metadata_account, get_metaplex_master_edition};
-
     let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
-    let metadata = get_metaplex_metadata_account(&metaplex_token_metadata::ID, &nft_mint);
-    let master_edition = get_metaplex_master_edition(&metaplex_token_metadata::ID, &nft_mint);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(sysvar::clock::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(nft_auction, false),
+        AccountMeta::new(nft_auction_authority, false),
+        AccountMeta::new_readonly(admin, true),
+    ];
+    if let Some(payment_mint) = payment_mint {
+        accounts.push(AccountMeta::new(get_associated_token_address(&receipt, &payment_mint), false));
+        accounts.extend(escrow_account_metas(&nft_auction_authority, &payment_mint));
+        if let Some(seller) = seller {
+            accounts.push(AccountMeta::new(get_associated_token_address(&seller, &payment_mint), false));
+        }
+    } else {
+        accounts.push(AccountMeta::new(receipt, false));
+        if let Some(seller) = seller {
+            accounts.push(AccountMeta::new(seller, false));
+        }
+    }
 
     Instruction {
         program_id: ID,
-        accounts: vec![
-            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        accounts,
+        data: VoilaInstruction::WithdrawFromNFTAuction.pack(),
+    }
+}
+
+fn bid_account_metas(
+    nft_auction: Pubkey,
+    bidder: Pubkey,
+    bid_history_page: u32,
+    key_gate: Option<Pubkey>,
+    payment_mint: Option<Pubkey>,
+) -> Vec<AccountMeta> {
+    let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
+    let (bid_escrow, _, _, _, _) = get_bid_escrow_pda(&nft_auction, &bidder, &ID);
+    // a leader raised by its proxy maximum is recorded too, which may start the next page
+    let (next_bid_history_page, _, _, _, _) = get_bid_history_page_pda(&nft_auction, bid_history_page + 1, &ID);
+    let (bid_history_page, _, _, _, _) = get_bid_history_page_pda(&nft_auction, bid_history_page, &ID);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(sysvar::clock::ID, false),
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(nft_auction, false),
+        AccountMeta::new(nft_auction_authority, false),
+        AccountMeta::new(bidder, true),
+        AccountMeta::new(bid_escrow, false),
+        AccountMeta::new(bid_history_page, false),
+        AccountMeta::new(next_bid_history_page, false),
+    ];
+    if let Some(key_gate) = key_gate {
+        let (user_key_record, _, _, _) = get_user_key_record_pda(&key_gate, &bidder, &ID);
+        accounts.push(AccountMeta::new_readonly(user_key_record, false));
+    }
+    if let Some(payment_mint) = payment_mint {
+        accounts.extend(escrow_account_metas(&nft_auction_authority, &payment_mint));
+        accounts.push(AccountMeta::new(get_associated_token_address(&bidder, &payment_mint), false));
+    }
+
+    accounts
+}
+
+pub fn bid_in_nft_auction(
+    nft_auction: Pubkey,
+    bidder: Pubkey,
+    bid_history_page: u32,
+    key_gate: Option<Pubkey>,
+    raise_price: u64,
+    payment_mint: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: bid_account_metas(nft_auction, bidder, bid_history_page, key_gate, payment_mint),
+        data: VoilaInstruction::BidInNFTAuction(raise_price).pack(),
+    }
+}
+
+pub fn bid_with_max_in_nft_auction(
+    nft_auction: Pubkey,
+    bidder: Pubkey,
+    bid_history_page: u32,
+    key_gate: Option<Pubkey>,
+    max_price: u64,
+    payment_mint: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: bid_account_metas(nft_auction, bidder, bid_history_page, key_gate, payment_mint),
+        data: VoilaInstruction::BidWithMaxInNFTAuction(max_price).pack(),
+    }
+}
+
+pub fn buy_now_in_nft_auction(
+    nft_auction: Pubkey,
+    buyer: Pubkey,
+    bid_history_page: u32,
+    key_gate: Option<Pubkey>,
+    payment_mint: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: bid_account_metas(nft_auction, buyer, bid_history_page, key_gate, payment_mint),
+        data: VoilaInstruction::BuyNowInNFTAuction.pack(),
+    }
+}
+
+pub fn claim_from_nft_auction(
+    nft_auction: Pubkey,
+    owner: Pubkey,
+    index: u8,
+    consigned_mint: Option<Pubkey>,
+) -> Instruction {
+    let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
+    let nft_mint = if let Some(consigned_mint) = consigned_mint {
+        consigned_mint
+    } else {
+        get_auction_nft_mint_pda(&nft_auction_authority, index, &ID).0
+    };
+    let nft_account = get_associated_token_address(&owner, &nft_mint);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(sysvar::clock::ID, false),
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        AccountMeta::new(nft_auction, false),
+        AccountMeta::new_readonly(nft_auction_authority, false),
+        AccountMeta::new(owner, true),
+        AccountMeta::new(nft_mint, false),
+        AccountMeta::new(nft_account, false),
+    ];
+    if consigned_mint.is_some() {
+        accounts.push(AccountMeta::new(get_associated_token_address(&nft_auction_authority, &nft_mint), false));
+    }
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: VoilaInstruction::ClaimNFTFromAuction(index).pack(),
+    }
+}
+
+pub fn settle_nft_auction(
+    nft_auction: Pubkey,
+    cranker: Pubkey,
+    winner: Pubkey,
+    index: u8,
+    consigned_mint: Option<Pubkey>,
+    payment_mint: Option<Pubkey>,
+) -> Instruction {
+    let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
+    let nft_mint = if let Some(consigned_mint) = consigned_mint {
+        consigned_mint
+    } else {
+        get_auction_nft_mint_pda(&nft_auction_authority, index, &ID).0
+    };
+    let nft_account = get_associated_token_address(&winner, &nft_mint);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(sysvar::clock::ID, false),
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        AccountMeta::new(nft_auction, false),
+        AccountMeta::new(nft_auction_authority, false),
+        AccountMeta::new(cranker, true),
+        AccountMeta::new_readonly(winner, false),
+        AccountMeta::new(nft_mint, false),
+        AccountMeta::new(nft_account, false),
+    ];
+    if consigned_mint.is_some() {
+        accounts.push(AccountMeta::new(get_associated_token_address(&nft_auction_authority, &nft_mint), false));
+    }
+    if let Some(payment_mint) = payment_mint {
+        accounts.extend(escrow_account_metas(&nft_auction_authority, &payment_mint));
+        accounts.push(AccountMeta::new(get_associated_token_address(&cranker, &payment_mint), false));
+    }
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: VoilaInstruction::SettleNFTAuction(index).pack(),
+    }
+}
+
+pub fn reclaim_consigned_nft(
+    nft_auction: Pubkey,
+    seller: Pubkey,
+    nft_mint: Pubkey,
+) -> Instruction {
+    let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new(nft_auction, false),
+            AccountMeta::new_readonly(nft_auction_authority, false),
+            AccountMeta::new(seller, true),
+            AccountMeta::new_readonly(nft_mint, false),
+            AccountMeta::new(get_associated_token_address(&seller, &nft_mint), false),
+            AccountMeta::new(get_associated_token_address(&nft_auction_authority, &nft_mint), false),
+        ],
+        data: VoilaInstruction::ReclaimConsignedNFT.pack(),
+    }
+}
+
+pub fn claim_bid_refund(
+    nft_auction: Pubkey,
+    bidder: Pubkey,
+    payment_mint: Option<Pubkey>,
+) -> Instruction {
+    let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
+    let (bid_escrow, _, _, _, _) = get_bid_escrow_pda(&nft_auction, &bidder, &ID);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(sysvar::clock::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(nft_auction, false),
+        AccountMeta::new(nft_auction_authority, false),
+        AccountMeta::new(bid_escrow, false),
+        AccountMeta::new(bidder, false),
+    ];
+    if let Some(payment_mint) = payment_mint {
+        accounts.extend(escrow_account_metas(&nft_auction_authority, &payment_mint));
+        accounts.push(AccountMeta::new(get_associated_token_address(&bidder, &payment_mint), false));
+    }
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: VoilaInstruction::ClaimBidRefund.pack(),
+    }
+}
+
+#[cfg(feature = "metaplex")]
+pub fn bind_auction_nft_on_metaplex(
+    nft_auction: Pubkey,
+    index: u8,
+    user_authority: Pubkey,
+    collection: bool,
+) -> Instruction {
+    use crate::nft::metaplex::{get_metaplex_metadata_account, get_metaplex_master_edition};
+
+    let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
+    let (nft_mint, _, _, _) = get_auction_nft_mint_pda(&nft_auction_authority, index, &ID);
+    let metadata = get_metaplex_metadata_account(&mpl_token_metadata::ID, &nft_mint);
+    let master_edition = get_metaplex_master_edition(&mpl_token_metadata::ID, &nft_mint);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+        AccountMeta::new_readonly(nft_auction, false),
+        AccountMeta::new(nft_auction_authority, false),
+        AccountMeta::new(nft_mint, false),
+        AccountMeta::new(metadata, false),
+        AccountMeta::new(master_edition, false),
+        AccountMeta::new(user_authority, true),
+    ];
+    if collection {
+        let (collection_mint, _, _, _) = get_collection_mint_pda(&nft_auction_authority, &ID);
+        accounts.extend([
+            AccountMeta::new_readonly(collection_mint, false),
+            AccountMeta::new_readonly(get_metaplex_metadata_account(&mpl_token_metadata::ID, &collection_mint), false),
+            AccountMeta::new_readonly(get_metaplex_master_edition(&mpl_token_metadata::ID, &collection_mint), false),
+        ]);
+    }
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: VoilaInstruction::BindAuctionNFTOnMetaplex(index).pack(),
+    }
+}
+
+#[cfg(feature = "metaplex")]
+pub fn create_nft_auction_collection(
+    nft_auction: Pubkey,
+    admin: Pubkey,
+) -> Instruction {
+    use crate::nft::metaplex::{get_metaplex_metadata_account, get_metaplex_master_edition};
+
+    let (nft_auction_authority, _, _) = get_nft_auction_authority_pda(&nft_auction, &ID);
+    let (collection_mint, _, _, _) = get_collection_mint_pda(&nft_auction_authority, &ID);
+    let collection_account = get_associated_token_address(&nft_auction_authority, &collection_mint);
+    let metadata = get_metaplex_metadata_account(&mpl_token_metadata::ID, &collection_mint);
+    let master_edition = get_metaplex_master_edition(&mpl_token_metadata::ID, &collection_mint);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
             AccountMeta::new_readonly(system_program::ID, false),
             AccountMeta::new_readonly(spl_token::ID, false),
-            AccountMeta::new_readonly(metaplex_token_metadata::ID, false),
-            AccountMeta::new_readonly(nft_auction, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+            AccountMeta::new(nft_auction, false),
             AccountMeta::new_readonly(nft_auction_authority, false),
+            AccountMeta::new(collection_mint, false),
+            AccountMeta::new(collection_account, false),
+            AccountMeta::new(metadata, false),
+            AccountMeta::new(master_edition, false),
+            AccountMeta::new(admin, true),
+        ],
+        data: VoilaInstruction::CreateNFTAuctionCollection.pack(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_dutch_auction(
+    admin_authority: Pubkey,
+    sn: u16,
+    receipt: Pubkey,
+    start_time: UnixTimestamp,
+    end_time: UnixTimestamp,
+    start_price: u64,
+    floor_price: u64,
+    price_decay: PriceDecay,
+    name: String,
+    uri: String,
+) -> Instruction {
+    let (dutch_auction, _, _, _, _)
+        = get_dutch_auction_pda(&admin_authority, sn, &ID);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(dutch_auction, false),
+            AccountMeta::new(admin_authority, true),
+        ],
+        data: VoilaInstruction::CreateDutchAuction(
+            sn,
+            receipt,
+            start_time,
+            end_time,
+            start_price,
+            floor_price,
+            price_decay,
+            name,
+            uri,
+        ).pack(),
+    }
+}
+
+pub fn buy_in_dutch_auction(
+    dutch_auction: Pubkey,
+    receipt: Pubkey,
+    buyer: Pubkey,
+) -> Instruction {
+    let (dutch_auction_authority, _, _) = get_nft_auction_authority_pda(&dutch_auction, &ID);
+    let (nft_mint, _, _, _) = get_auction_nft_mint_pda(&dutch_auction_authority, 0, &ID);
+    let nft_account = get_associated_token_address(&buyer, &nft_mint);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new(dutch_auction, false),
+            AccountMeta::new_readonly(dutch_auction_authority, false),
+            AccountMeta::new(receipt, false),
+            AccountMeta::new(buyer, true),
+            AccountMeta::new(nft_mint, false),
+            AccountMeta::new(nft_account, false),
+        ],
+        data: VoilaInstruction::BuyInDutchAuction.pack(),
+    }
+}
+
+#[cfg(feature = "metaplex")]
+pub fn bind_dutch_auction_nft_on_metaplex(
+    dutch_auction: Pubkey,
+    user_authority: Pubkey,
+) -> Instruction {
+    use crate::nft::metaplex::{get_metaplex_metadata_account, get_metaplex_master_edition};
+
+    let (dutch_auction_authority, _, _) = get_nft_auction_authority_pda(&dutch_auction, &ID);
+    let (nft_mint, _, _, _) = get_auction_nft_mint_pda(&dutch_auction_authority, 0, &ID);
+    let metadata = get_metaplex_metadata_account(&mpl_token_metadata::ID, &nft_mint);
+    let master_edition = get_metaplex_master_edition(&mpl_token_metadata::ID, &nft_mint);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+            AccountMeta::new_readonly(dutch_auction, false),
+            AccountMeta::new(dutch_auction_authority, false),
+            AccountMeta::new(nft_mint, false),
+            AccountMeta::new(metadata, false),
+            AccountMeta::new(master_edition, false),
+            AccountMeta::new(user_authority, true),
+        ],
+        data: VoilaInstruction::BindDutchAuctionNFTOnMetaplex.pack(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_sealed_auction(
+    admin_authority: Pubkey,
+    sn: u16,
+    receipt: Pubkey,
+    kind: SealedAuctionKind,
+    start_time: UnixTimestamp,
+    commit_end_time: UnixTimestamp,
+    reveal_end_time: UnixTimestamp,
+    min_price: u64,
+    slash_bps: u16,
+    name: String,
+    uri: String,
+) -> Instruction {
+    let (sealed_auction, _, _, _, _)
+        = get_sealed_auction_pda(&admin_authority, sn, &ID);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(sealed_auction, false),
+            AccountMeta::new(admin_authority, true),
+        ],
+        data: VoilaInstruction::CreateSealedAuction(
+            sn,
+            receipt,
+            kind,
+            start_time,
+            commit_end_time,
+            reveal_end_time,
+            min_price,
+            slash_bps,
+            name,
+            uri,
+        ).pack(),
+    }
+}
+
+pub fn commit_sealed_bid(
+    sealed_auction: Pubkey,
+    bidder: Pubkey,
+    commitment: [u8; 32],
+    deposit: u64,
+) -> Instruction {
+    let (sealed_auction_authority, _, _) = get_nft_auction_authority_pda(&sealed_auction, &ID);
+    let (sealed_bid, _, _, _, _) = get_sealed_bid_pda(&sealed_auction, &bidder, &ID);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new(sealed_auction, false),
+            AccountMeta::new(sealed_auction_authority, false),
+            AccountMeta::new(sealed_bid, false),
+            AccountMeta::new(bidder, true),
+        ],
+        data: VoilaInstruction::CommitSealedBid(commitment, deposit).pack(),
+    }
+}
+
+pub fn reveal_sealed_bid(
+    sealed_auction: Pubkey,
+    bidder: Pubkey,
+    price: u64,
+    salt: [u8; 32],
+) -> Instruction {
+    let (sealed_bid, _, _, _, _) = get_sealed_bid_pda(&sealed_auction, &bidder, &ID);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new(sealed_auction, false),
+            AccountMeta::new(sealed_bid, false),
+            AccountMeta::new_readonly(bidder, true),
+        ],
+        data: VoilaInstruction::RevealSealedBid(price, salt).pack(),
+    }
+}
+
+pub fn settle_sealed_auction(
+    sealed_auction: Pubkey,
+    receipt: Pubkey,
+    cranker: Pubkey,
+    winner: Pubkey,
+) -> Instruction {
+    let (sealed_auction_authority, _, _) = get_nft_auction_authority_pda(&sealed_auction, &ID);
+    let (sealed_bid, _, _, _, _) = get_sealed_bid_pda(&sealed_auction, &winner, &ID);
+    let (nft_mint, _, _, _) = get_auction_nft_mint_pda(&sealed_auction_authority, 0, &ID);
+    let nft_account = get_associated_token_address(&winner, &nft_mint);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new(sealed_auction, false),
+            AccountMeta::new(sealed_auction_authority, false),
+            AccountMeta::new(sealed_bid, false),
+            AccountMeta::new(receipt, false),
+            AccountMeta::new(cranker, true),
+            AccountMeta::new(winner, false),
+            AccountMeta::new(nft_mint, false),
+            AccountMeta::new(nft_account, false),
+        ],
+        data: VoilaInstruction::SettleSealedAuction.pack(),
+    }
+}
+
+pub fn refund_sealed_bid(
+    sealed_auction: Pubkey,
+    receipt: Pubkey,
+    bidder: Pubkey,
+) -> Instruction {
+    let (sealed_auction_authority, _, _) = get_nft_auction_authority_pda(&sealed_auction, &ID);
+    let (sealed_bid, _, _, _, _) = get_sealed_bid_pda(&sealed_auction, &bidder, &ID);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(sealed_auction, false),
+            AccountMeta::new(sealed_auction_authority, false),
+            AccountMeta::new(sealed_bid, false),
+            AccountMeta::new(receipt, false),
+            AccountMeta::new(bidder, false),
+        ],
+        data: VoilaInstruction::RefundSealedBid.pack(),
+    }
+}
+
+#[cfg(feature = "metaplex")]
+pub fn bind_sealed_auction_nft_on_metaplex(
+    sealed_auction: Pubkey,
+    user_authority: Pubkey,
+) -> Instruction {
+    use crate::nft::metaplex::{get_metaplex_metadata_account, get_metaplex_master_edition};
+
+    let (sealed_auction_authority, _, _) = get_nft_auction_authority_pda(&sealed_auction, &ID);
+    let (nft_mint, _, _, _) = get_auction_nft_mint_pda(&sealed_auction_authority, 0, &ID);
+    let metadata = get_metaplex_metadata_account(&mpl_token_metadata::ID, &nft_mint);
+    let master_edition = get_metaplex_master_edition(&mpl_token_metadata::ID, &nft_mint);
+
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+            AccountMeta::new_readonly(sealed_auction, false),
+            AccountMeta::new(sealed_auction_authority, false),
             AccountMeta::new(nft_mint, false),
             AccountMeta::new(metadata, false),
             AccountMeta::new(master_edition, false),
             AccountMeta::new(user_authority, true),
         ],
-        data: VoilaInstruction::BindAuctionNFTOnMetaplex.pack(),
+        data: VoilaInstruction::BindSealedAuctionNFTOnMetaplex.pack(),
     }
 }
\ No newline at end of file
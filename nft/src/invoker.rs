@@ -81,6 +81,32 @@ pub fn process_optimal_create_account<'a>(
     )
 }
 
+/// Grow a program owned account to `data_len`, the authority tops up the rent
+#[inline(never)]
+pub fn process_realloc_account<'a>(
+    rent_info: &AccountInfo<'a>,
+    target_account_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    data_len: usize,
+) -> ProgramResult {
+    let required_lamports = Rent::from_account_info(rent_info)?
+        .minimum_balance(data_len)
+        .saturating_sub(target_account_info.lamports());
+
+    if required_lamports > 0 {
+        process_transfer(
+            authority_info,
+            target_account_info,
+            system_program_info,
+            required_lamports,
+            &[],
+        )?;
+    }
+
+    target_account_info.realloc(data_len, true)
+}
+
 /// Invoke signed unless signers seeds are empty
 #[inline(always)]
 pub fn invoke_optionally_signed(
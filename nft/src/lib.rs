@@ -44,12 +44,18 @@ pub trait Updater<P> {
 pub trait Packer: IsInitialized + BorshSerialize + BorshDeserialize {
     const LEN: usize;
 
-    fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+    fn unpack_unchecked(data: &[u8]) -> Result<Self, ProgramError> {
         if data.len() < Self::LEN {
             return Err(ProgramError::InvalidAccountData);
         }
 
         let account: Self = BorshDeserialize::deserialize(&mut data.as_ref())?;
+
+        Ok(account)
+    }
+
+    fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let account = Self::unpack_unchecked(data)?;
         if account.is_initialized() {
             Ok(account)
         } else {
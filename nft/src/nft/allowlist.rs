@@ -0,0 +1,143 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use solana_program::{hash::hashv, pubkey::Pubkey};
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Proof that a wallet is in the allowlist, `allowance` is 0 if the wallet has no own limit.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct AllowlistProof {
+    pub allowance: u16,
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl AllowlistProof {
+    pub fn verify(&self, root: &[u8; 32], wallet: &Pubkey) -> bool {
+        let node = self.proof
+            .iter()
+            .fold(allowlist_leaf(wallet, self.allowance), |node, sibling| allowlist_node(&node, sibling));
+
+        &node == root
+    }
+}
+
+#[inline]
+pub fn allowlist_leaf(wallet: &Pubkey, allowance: u16) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, wallet.as_ref(), &allowance.to_le_bytes()]).to_bytes()
+}
+
+/// Hash of two sibling nodes, sorted so that proofs do not carry positions
+#[inline]
+pub fn allowlist_node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        hashv(&[NODE_PREFIX, a, b]).to_bytes()
+    } else {
+        hashv(&[NODE_PREFIX, b, a]).to_bytes()
+    }
+}
+
+/// Builds the allowlist tree bottom-up from the leaves to the root, an odd node is promoted to the next layer
+pub fn build_tree(entries: &[(Pubkey, u16)]) -> Vec<Vec<[u8; 32]>> {
+    let mut layers = vec![entries
+        .iter()
+        .map(|(wallet, allowance)| allowlist_leaf(wallet, *allowance))
+        .collect::<Vec<_>>()];
+    while layers.last().unwrap().len() > 1 {
+        let layer = layers
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => allowlist_node(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        layers.push(layer);
+    }
+
+    layers
+}
+
+/// Proof of the leaf at `index` in the layers returned by `build_tree`
+pub fn build_proof(layers: &[Vec<[u8; 32]>], mut index: usize, allowance: u16) -> AllowlistProof {
+    let mut proof = Vec::with_capacity(layers.len());
+    for layer in &layers[..layers.len() - 1] {
+        if let Some(sibling) = layer.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+
+    AllowlistProof { allowance, proof }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallets(n: usize) -> Vec<(Pubkey, u16)> {
+        (0..n).map(|i| (Pubkey::new_unique(), i as u16)).collect()
+    }
+
+    #[test]
+    fn test_verify_even_tree() {
+        let entries = wallets(8);
+        let layers = build_tree(&entries);
+        let root = layers.last().unwrap()[0];
+
+        for (index, (wallet, allowance)) in entries.iter().enumerate() {
+            assert!(build_proof(&layers, index, *allowance).verify(&root, wallet));
+        }
+    }
+
+    #[test]
+    fn test_verify_odd_tree() {
+        let entries = wallets(5);
+        let layers = build_tree(&entries);
+        let root = layers.last().unwrap()[0];
+
+        for (index, (wallet, allowance)) in entries.iter().enumerate() {
+            assert!(build_proof(&layers, index, *allowance).verify(&root, wallet));
+        }
+        // the promoted last node has a shorter proof
+        assert_eq!(build_proof(&layers, 4, 4).proof.len(), 1);
+    }
+
+    #[test]
+    fn test_verify_wrong_allowance() {
+        let entries = wallets(4);
+        let layers = build_tree(&entries);
+        let root = layers.last().unwrap()[0];
+
+        let (wallet, allowance) = entries[1];
+        assert!(!build_proof(&layers, 1, allowance + 1).verify(&root, &wallet));
+    }
+
+    #[test]
+    fn test_verify_wrong_buyer() {
+        let entries = wallets(4);
+        let layers = build_tree(&entries);
+        let root = layers.last().unwrap()[0];
+
+        let (_, allowance) = entries[2];
+        assert!(!build_proof(&layers, 2, allowance).verify(&root, &entries[3].0));
+        assert!(!build_proof(&layers, 2, allowance).verify(&root, &Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_verify_tampered_proof() {
+        let entries = wallets(6);
+        let layers = build_tree(&entries);
+        let root = layers.last().unwrap()[0];
+
+        let (wallet, allowance) = entries[3];
+        let mut tampered = build_proof(&layers, 3, allowance);
+        tampered.proof[1][0] ^= 1;
+        assert!(!tampered.verify(&root, &wallet));
+
+        let mut truncated = build_proof(&layers, 3, allowance);
+        truncated.proof.pop();
+        assert!(!truncated.verify(&root, &wallet));
+    }
+}
@@ -2,9 +2,14 @@ use borsh::{BorshSerialize, BorshDeserialize};
 use serde::{Serialize, Deserialize};
 use solana_program::{msg, pubkey::Pubkey, clock::UnixTimestamp, program_error::ProgramError, program_pack::IsInitialized, entrypoint::ProgramResult};
 
-use crate::{pda::get_nft_auction_authority_pda, error::VoilaError, Packer};
+use crate::{pda::get_nft_auction_authority_pda, error::VoilaError, Packer, Updater};
+
+use super::{Royalty, is_valid_metadata};
 
 const PREVIOUS_BIDDERS_LEN: usize = 6;
+pub const BID_HISTORY_PAGE_LEN: usize = 20;
+pub const MAX_AUCTION_WINNERS: u8 = 16;
+pub const MAX_MIN_RAISE_BPS: u16 = 10000;
 
 #[derive(Clone, Copy, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 pub struct BidInfo {
@@ -13,19 +18,128 @@ pub struct BidInfo {
     pub timestamp: UnixTimestamp,
 }
 
+/// Anti-sniping rule: any bid placed within `window` seconds before `end_time`
+/// pushes `end_time` out by `duration` seconds, never beyond `max_end_time`.
+#[derive(Clone, Copy, Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct BidExtension {
+    pub window: u32,
+    pub duration: u32,
+    pub max_end_time: Option<UnixTimestamp>,
+}
+
+impl BidExtension {
+    pub fn is_valid(&self, end_time: UnixTimestamp) -> bool {
+        self.window > 0
+            && self.duration > 0
+            && self.max_end_time.is_none_or(|max_end_time| max_end_time >= end_time)
+    }
+
+    fn extend(&self, end_time: UnixTimestamp, timestamp: UnixTimestamp) -> Result<UnixTimestamp, ProgramError> {
+        let window_start = end_time
+            .checked_sub(self.window as UnixTimestamp)
+            .ok_or(VoilaError::MathOverflow)?;
+        if timestamp < window_start {
+            return Ok(end_time);
+        }
+
+        let extended_end_time = end_time
+            .checked_add(self.duration as UnixTimestamp)
+            .ok_or(VoilaError::MathOverflow)?;
+        if let Some(max_end_time) = self.max_end_time {
+            Ok(extended_end_time.min(max_end_time).max(end_time))
+        } else {
+            Ok(extended_end_time)
+        }
+    }
+}
+
+/// Minimum winning price; a hidden reserve is left out of the decoded auction info.
+#[derive(Clone, Copy, Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct ReservePrice {
+    pub price: u64,
+    /// Only masks the price in the decoded auction info and program logs, not on-chain secrecy:
+    /// the price is stored in plaintext and anyone can read it from the account data.
+    pub hidden: bool,
+}
+
+/// An existing NFT deposited by `seller` into the auction escrow instead of minting a new one,
+/// the seller receives the proceeds minus the house fee.
+#[derive(Clone, Copy, Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Consignment {
+    pub mint: Pubkey,
+    pub seller: Pubkey,
+    pub house_fee_bps: u16,
+}
+
+impl Consignment {
+    pub fn is_valid(&self) -> bool {
+        self.house_fee_bps <= 10000
+    }
+
+    pub fn house_fee(&self, proceeds: u64) -> Result<u64, ProgramError> {
+        let house_fee = (proceeds as u128)
+            .checked_mul(self.house_fee_bps as u128)
+            .ok_or(VoilaError::MathOverflow)?
+            / 10000;
+
+        Ok(house_fee as u64)
+    }
+}
+
+/// Fields of an auction that the admin may change before the first bid
+#[derive(Clone, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct NFTAuctionUpdate {
+    pub start_time: Option<UnixTimestamp>,
+    pub end_time: Option<UnixTimestamp>,
+    pub base_price: Option<u64>,
+    pub min_raise_price: Option<u64>,
+    pub min_raise_bps: Option<u16>,
+    pub uri: Option<String>,
+}
+
 #[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 pub struct NFTAuction {
     pub is_initialized: bool,
     pub admin: Pubkey,
+    pub receipt: Pubkey,
     pub pda_authority: Pubkey,
     pub pda_seed: [u8; 1],
     pub start_time: UnixTimestamp,
     pub end_time: UnixTimestamp,
     pub base_price: u64,
     pub min_raise_price: u64,
-    pub current_bid_info: Option<BidInfo>,
+    /// Minimum raise in basis points of the price being outbid, the larger of this and `min_raise_price` applies
+    pub min_raise_bps: u16,
+    /// Number of identical pieces, each of the top `winners` bids wins one
+    pub winners: u8,
+    pub bid_extension: Option<BidExtension>,
+    pub reserve_price: Option<ReservePrice>,
+    pub buy_now_price: Option<u64>,
+    /// Bids are paid in this SPL token instead of lamports
+    pub payment_mint: Option<Pubkey>,
+    pub consignment: Option<Consignment>,
+    /// Paid to whoever settles a winning bid on behalf of the winner
+    pub crank_reward: Option<u64>,
+    pub cranked: u8,
+    /// Only holders of a `UserKeyRecord` of this key info can bid
+    pub key_gate: Option<Pubkey>,
+    pub royalty: Option<Royalty>,
+    /// Mint of the Metaplex collection NFT, set once the collection is created
+    pub collection: Option<Pubkey>,
+    /// Maximum of the leader placed by proxy bidding, it is public since the whole maximum
+    /// is escrowed in the leader's `BidEscrow`
+    pub proxy_max_price: Option<u64>,
+    /// Winning bids ordered from the highest to the lowest price
+    pub winning_bids: Vec<BidInfo>,
     pub previous_bid_infos: Vec<BidInfo>,
-    pub claimed: bool,
+    /// Total entries of the bid history, including leaders raised by their proxy maximum,
+    /// full history is kept in `BidHistoryPage` accounts
+    pub bid_count: u32,
+    /// Claim state of each winning slot
+    pub claimed: Vec<bool>,
+    pub withdrawn: bool,
+    /// Cancelled by the admin, the account is kept so that its sn, escrows and history pages are never reused
+    pub cancelled: bool,
     pub name: String,
     pub uri: String,
 }
@@ -34,12 +148,23 @@ impl NFTAuction {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         admin: Pubkey,
+        receipt: Pubkey,
         nft_auction: &Pubkey,
         program_id: &Pubkey,
         start_time: UnixTimestamp,
         end_time: UnixTimestamp,
         base_price: u64,
         min_raise_price: u64,
+        min_raise_bps: u16,
+        winners: u8,
+        bid_extension: Option<BidExtension>,
+        reserve_price: Option<ReservePrice>,
+        buy_now_price: Option<u64>,
+        payment_mint: Option<Pubkey>,
+        consignment: Option<Consignment>,
+        crank_reward: Option<u64>,
+        key_gate: Option<Pubkey>,
+        royalty: Option<Royalty>,
         name: String,
         uri: String,
     ) -> Self {
@@ -49,15 +174,32 @@ impl NFTAuction {
         Self {
             is_initialized: true,
             admin,
+            receipt,
             pda_authority,
             pda_seed,
             start_time,
             end_time,
             base_price,
             min_raise_price,
-            current_bid_info: None,
+            min_raise_bps,
+            winners,
+            bid_extension,
+            reserve_price,
+            buy_now_price,
+            payment_mint,
+            consignment,
+            crank_reward,
+            cranked: 0,
+            key_gate,
+            royalty,
+            collection: None,
+            proxy_max_price: None,
+            winning_bids: Vec::with_capacity(winners as usize),
             previous_bid_infos: Vec::new(),
-            claimed: false,
+            bid_count: 0,
+            claimed: vec![false; winners as usize],
+            withdrawn: false,
+            cancelled: false,
             name,
             uri,
         }
@@ -68,67 +210,476 @@ impl NFTAuction {
         [nft_auction.as_ref(), &self.pda_seed]
     }
 
+    /// Index of the history page that the next bid is appended to
+    #[inline]
+    pub fn bid_history_page(&self) -> u32 {
+        self.bid_count / BID_HISTORY_PAGE_LEN as u32
+    }
+
+    #[inline]
+    pub fn highest_bid(&self) -> Option<&BidInfo> {
+        self.winning_bids.first()
+    }
+
+    /// The leader's bid after its proxy maximum raised it over a bid of `bidder`,
+    /// `highest_price` is the leading price before that bid
+    pub fn raised_leader_bid(&self, bidder: &Pubkey, highest_price: Option<u64>, timestamp: UnixTimestamp) -> Option<BidInfo> {
+        self.highest_bid()
+            .filter(|leader_bid_info| &leader_bid_info.bidder != bidder && Some(leader_bid_info.price) != highest_price)
+            .map(|leader_bid_info| BidInfo { timestamp, ..*leader_bid_info })
+    }
+
+    /// The bid that will be displaced next, only once all winning slots are taken
+    pub fn lowest_winning_bid(&self) -> Option<&BidInfo> {
+        if self.winning_bids.len() < self.winners as usize {
+            None
+        } else {
+            self.winning_bids.last()
+        }
+    }
+
+    /// Price that a new bid raises from
+    pub fn floor_price(&self) -> u64 {
+        self.lowest_winning_bid().map_or(self.base_price, |bid_info| bid_info.price)
+    }
+
+    fn min_raise_from(&self, price: u64) -> Result<u64, ProgramError> {
+        let bps_raise = (price as u128)
+            .checked_mul(self.min_raise_bps as u128)
+            .ok_or(VoilaError::MathOverflow)?
+            / 10000;
+        let bps_raise = u64::try_from(bps_raise).map_err(|_| VoilaError::MathOverflow)?;
+
+        Ok(self.min_raise_price.max(bps_raise))
+    }
+
+    /// Minimum raise over the current floor price
+    pub fn min_raise(&self) -> Result<u64, ProgramError> {
+        self.min_raise_from(self.floor_price())
+    }
+
+    /// Lowest price that a new bid can be placed at
+    pub fn min_next_bid(&self) -> Result<u64, ProgramError> {
+        self.floor_price()
+            .checked_add(self.min_raise()?)
+            .ok_or_else(|| VoilaError::MathOverflow.into())
+    }
+
+    fn is_bid_reserve_met(&self, bid_info: &BidInfo) -> bool {
+        self.reserve_price.is_none_or(|reserve_price| bid_info.price >= reserve_price.price)
+    }
+
+    pub fn is_reserve_met(&self) -> bool {
+        match (&self.reserve_price, self.highest_bid()) {
+            (Some(_), Some(bid_info)) => self.is_bid_reserve_met(bid_info),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    fn check_bid_time(&self, timestamp: UnixTimestamp) -> ProgramResult {
+        if self.cancelled {
+            msg!("Auction is cancelled");
+            Err(VoilaError::InvalidBidTime.into())
+        } else if timestamp < self.start_time {
+            msg!("Auction is not started yet");
+            Err(VoilaError::InvalidBidTime.into())
+        } else if timestamp >= self.end_time {
+            msg!("Auction is end of bidding");
+            Err(VoilaError::InvalidBidTime.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Insert a bid into the winning slots, returns the displaced bid if all slots were taken
+    fn place_bid(&mut self, bid_info: BidInfo) -> Option<BidInfo> {
+        let displaced_bid_info = if self.winning_bids.len() >= self.winners as usize {
+            self.winning_bids.pop()
+        } else {
+            None
+        };
+        if let Some(displaced_bid_info) = displaced_bid_info {
+            self.previous_bid_infos.insert(0, displaced_bid_info);
+            self.previous_bid_infos.truncate(PREVIOUS_BIDDERS_LEN);
+        }
+
+        // earlier bids keep the higher rank on equal prices
+        let index = self.winning_bids
+            .iter()
+            .position(|winning_bid| winning_bid.price < bid_info.price)
+            .unwrap_or(self.winning_bids.len());
+        self.winning_bids.insert(index, bid_info);
+
+        displaced_bid_info
+    }
+
+    /// Resolve a bid with its maximum price against the proxy maximum of the leader,
+    /// only used in single winner auction
+    fn resolve_proxy_bid(&mut self, bid_info: BidInfo, max_price: u64) -> Result<(BidInfo, Option<BidInfo>), ProgramError> {
+        let leader_bid_info = if let Some(leader_bid_info) = self.highest_bid() {
+            *leader_bid_info
+        } else {
+            self.place_bid(bid_info);
+            self.proxy_max_price = if max_price > bid_info.price { Some(max_price) } else { None };
+
+            return Ok((bid_info, None));
+        };
+        // the leader raising its own visible bid is never resolved against its own maximum
+        if leader_bid_info.bidder == bid_info.bidder {
+            self.winning_bids[0] = bid_info;
+            if self.proxy_max_price.is_some_and(|proxy_max_price| proxy_max_price <= bid_info.price) {
+                self.proxy_max_price = None;
+            }
+
+            return Ok((bid_info, None));
+        }
+        let leader_max_price = self.proxy_max_price.unwrap_or(leader_bid_info.price);
+
+        if max_price > leader_max_price {
+            let price = max_price
+                .min(leader_max_price.saturating_add(self.min_raise_from(leader_max_price)?))
+                .max(bid_info.price);
+            let new_bid_info = BidInfo { price, ..bid_info };
+            let displaced_bid_info = self.place_bid(new_bid_info);
+            self.proxy_max_price = if max_price > price { Some(max_price) } else { None };
+
+            Ok((new_bid_info, displaced_bid_info))
+        } else {
+            // leader keeps the lead and is raised automatically up to its maximum
+            let price = leader_max_price
+                .min(max_price.saturating_add(self.min_raise_from(max_price)?))
+                .max(leader_bid_info.price);
+            self.winning_bids[0].price = price;
+            if self.proxy_max_price.is_some_and(|proxy_max_price| proxy_max_price <= price) {
+                self.proxy_max_price = None;
+            }
+
+            let outbid_bid_info = BidInfo { price: max_price, ..bid_info };
+            self.previous_bid_infos.insert(0, outbid_bid_info);
+            self.previous_bid_infos.truncate(PREVIOUS_BIDDERS_LEN);
+
+            Ok((outbid_bid_info, None))
+        }
+    }
+
+    fn extend_end_time(&mut self, timestamp: UnixTimestamp) -> ProgramResult {
+        if let Some(bid_extension) = &self.bid_extension {
+            let end_time = bid_extension.extend(self.end_time, timestamp)?;
+            if end_time != self.end_time {
+                msg!("Auction end time is extended to {}", end_time);
+                self.end_time = end_time;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn bid(
         &mut self,
         raise_price: u64,
         timestamp: UnixTimestamp,
         bidder: Pubkey,
-    ) -> Result<Option<BidInfo>, ProgramError> {
-        if raise_price < self.min_raise_price {
+    ) -> Result<(BidInfo, Option<BidInfo>), ProgramError> {
+        if raise_price < self.min_raise()? {
             return Err(VoilaError::InvalidBidPrice.into());
         }
+        self.check_bid_time(timestamp)?;
 
-        if timestamp < self.start_time {
-            msg!("Auction is not started yet");
-            return Err(VoilaError::InvalidBidTime.into());
-        } else if timestamp > self.end_time {
-            msg!("Auction is end of bidding");
-            return Err(VoilaError::InvalidBidTime.into());
+        let new_bid_info = BidInfo {
+            bidder,
+            price: self.floor_price().checked_add(raise_price).ok_or(VoilaError::MathOverflow)?,
+            timestamp,
+        };
+        let result = if self.winners == 1 {
+            self.resolve_proxy_bid(new_bid_info, new_bid_info.price)?
+        } else {
+            (new_bid_info, self.place_bid(new_bid_info))
+        };
+        self.extend_end_time(timestamp)?;
+
+        Ok(result)
+    }
+
+    /// Bid the lowest valid price and let the program raise it by the minimum raise up to `max_price`
+    pub fn bid_with_max(
+        &mut self,
+        max_price: u64,
+        timestamp: UnixTimestamp,
+        bidder: Pubkey,
+    ) -> Result<(BidInfo, Option<BidInfo>), ProgramError> {
+        if self.winners != 1 {
+            msg!("Proxy bidding is only available in single winner auction");
+            return Err(VoilaError::InvalidParam.into());
         }
+        self.check_bid_time(timestamp)?;
 
-        let (last_bid_info, new_bid_info) = if let Some(last_bid_info) = self.current_bid_info {
-            self.previous_bid_infos.insert(0, last_bid_info);
-            self.previous_bid_infos.truncate(PREVIOUS_BIDDERS_LEN);
+        let min_price = self.min_next_bid()?;
+        if let Some(leader_bid_info) = self.highest_bid().copied() {
+            if leader_bid_info.bidder == bidder {
+                if max_price <= self.proxy_max_price.unwrap_or(leader_bid_info.price) {
+                    msg!("New maximum price should be higher than the current one");
+                    return Err(VoilaError::InvalidBidPrice.into());
+                }
+                self.proxy_max_price = Some(max_price);
 
-            let new_bid_info = BidInfo {
-                bidder,
-                price: last_bid_info.price.checked_add(raise_price).ok_or(VoilaError::MathOverflow)?,
-                timestamp,
-            };
+                return Ok((leader_bid_info, None));
+            }
+        }
+        if max_price < min_price {
+            msg!("Maximum price is lower than the minimum bid price {}", min_price);
+            return Err(VoilaError::InvalidBidPrice.into());
+        }
 
-            (Some(last_bid_info), new_bid_info)
-        } else {
-            let new_bid_info = BidInfo {
-                bidder,
-                price: self.base_price.checked_add(raise_price).ok_or(VoilaError::MathOverflow)?,
-                timestamp,
-            };
+        let new_bid_info = BidInfo {
+            bidder,
+            price: min_price,
+            timestamp,
+        };
+        let result = self.resolve_proxy_bid(new_bid_info, max_price)?;
+        self.extend_end_time(timestamp)?;
+
+        Ok(result)
+    }
+
+    pub fn buy_now(
+        &mut self,
+        timestamp: UnixTimestamp,
+        buyer: Pubkey,
+    ) -> Result<(BidInfo, Option<BidInfo>), ProgramError> {
+        let buy_now_price = self.buy_now_price.ok_or_else(|| {
+            msg!("Buy now is not enabled in this auction");
+            VoilaError::InvalidBidPrice
+        })?;
+        self.check_bid_time(timestamp)?;
 
-            (None, new_bid_info)
+        if let Some(highest_bid) = self.highest_bid() {
+            if highest_bid.price >= buy_now_price {
+                msg!("Current bid has already reached buy now price");
+                return Err(VoilaError::InvalidBidPrice.into());
+            }
+        }
+        let new_bid_info = BidInfo {
+            bidder: buyer,
+            price: buy_now_price,
+            timestamp,
         };
-        self.current_bid_info = Some(new_bid_info);
+        let displaced_bid_info = self.place_bid(new_bid_info);
+        self.proxy_max_price = None;
+        self.end_time = timestamp;
 
-        Ok(last_bid_info)
+        Ok((new_bid_info, displaced_bid_info))
     }
 
-    pub fn claim(&mut self, timestamp: UnixTimestamp, owner: &Pubkey) -> ProgramResult {
+    /// Mark the winning slot at `index` as delivered and return its bid
+    pub fn settle(&mut self, timestamp: UnixTimestamp, index: u8) -> Result<BidInfo, ProgramError> {
+        if self.cancelled {
+            msg!("Auction is cancelled");
+            return Err(VoilaError::NFTCannotClaim.into());
+        }
         if timestamp < self.end_time {
             msg!("Auction is not end of bidding yet");
             return Err(VoilaError::InvalidBidTime.into());
         }
 
-        if let Some(bid_info) = &self.current_bid_info {
-            if &bid_info.bidder == owner {
-                self.claimed = true;
+        let index = index as usize;
+        if let Some(bid_info) = self.winning_bids.get(index).copied() {
+            if !self.is_bid_reserve_met(&bid_info) {
+                msg!("Winning bid is below the reserve price");
+                return Err(VoilaError::ReserveNotMet.into());
+            }
+            if self.claimed[index] {
+                msg!("NFT of this winning bid is already claimed");
+                return Err(VoilaError::NFTCannotClaim.into());
+            }
+            self.claimed[index] = true;
+
+            Ok(bid_info)
+        } else {
+            msg!("No winning bid exists at index {}", index);
+            Err(VoilaError::NFTCannotClaim.into())
+        }
+    }
 
-                Ok(())
-            } else {
-                msg!("Only the latest bidder can claim the NFT");
-                Err(VoilaError::NFTCannotClaim.into())
+    pub fn claim(&mut self, timestamp: UnixTimestamp, owner: &Pubkey, index: u8) -> ProgramResult {
+        let bid_info = self.settle(timestamp, index)?;
+        if &bid_info.bidder != owner {
+            msg!("Only the winning bidder can claim the NFT");
+            return Err(VoilaError::NFTCannotClaim.into());
+        }
+
+        Ok(())
+    }
+
+    /// Settle the winning slot at `index` on behalf of the winner, returns the winner and the crank reward.
+    /// A winner settling its own slot gets no reward.
+    pub fn crank(&mut self, timestamp: UnixTimestamp, index: u8, cranker: &Pubkey) -> Result<(Pubkey, u64), ProgramError> {
+        let bid_info = self.settle(timestamp, index)?;
+        let crank_reward = if &bid_info.bidder == cranker {
+            0
+        } else {
+            self.crank_reward.unwrap_or(0)
+        };
+        if crank_reward > 0 {
+            self.cranked = self.cranked.checked_add(1).ok_or(VoilaError::MathOverflow)?;
+        }
+
+        Ok((bid_info.bidder, crank_reward))
+    }
+
+    /// Return the consigned NFT to the seller when no winning bid meets the reserve price
+    pub fn reclaim(&mut self, timestamp: UnixTimestamp, seller: &Pubkey) -> ProgramResult {
+        if self.cancelled {
+            msg!("Consigned NFT is already returned by the cancellation");
+            return Err(VoilaError::NFTCannotClaim.into());
+        }
+        if timestamp < self.end_time {
+            msg!("Auction is not end of bidding yet");
+            return Err(VoilaError::InvalidBidTime.into());
+        }
+
+        if let Some(consignment) = &self.consignment {
+            if &consignment.seller != seller {
+                msg!("Only the seller can reclaim the consigned NFT");
+                return Err(VoilaError::NFTCannotClaim.into());
             }
         } else {
+            msg!("NFT auction has no consigned NFT");
+            return Err(VoilaError::NFTCannotClaim.into());
+        }
+        if !self.winning_bids.is_empty() && self.is_reserve_met() {
+            msg!("Consigned NFT is won by the highest bidder");
+            return Err(VoilaError::NFTCannotClaim.into());
+        }
+        if self.claimed[0] {
+            msg!("Consigned NFT is already reclaimed");
+            return Err(VoilaError::NFTCannotClaim.into());
+        }
+        self.claimed[0] = true;
+
+        Ok(())
+    }
+
+    pub fn cancel(&mut self, timestamp: UnixTimestamp) -> ProgramResult {
+        if self.cancelled {
+            msg!("Auction is already cancelled");
+            return Err(VoilaError::InvalidParam.into());
+        }
+        if timestamp >= self.end_time {
+            msg!("Auction is already end of bidding");
+            return Err(VoilaError::InvalidBidTime.into());
+        }
+        self.cancelled = true;
+
+        Ok(())
+    }
+
+    /// Amount of the bidder's escrow that backs live or winning bids,
+    /// the leader's proxy maximum stays locked until the end of bidding
+    pub fn locked_amount(&self, timestamp: UnixTimestamp, bidder: &Pubkey) -> u64 {
+        if self.cancelled {
+            return 0;
+        }
+
+        self.winning_bids
+            .iter()
+            .enumerate()
+            .filter(|(_, bid_info)| &bid_info.bidder == bidder)
+            .filter(|(_, bid_info)| timestamp < self.end_time || self.is_bid_reserve_met(bid_info))
+            .map(|(index, bid_info)| match self.proxy_max_price {
+                Some(proxy_max_price) if index == 0 && timestamp < self.end_time => proxy_max_price,
+                _ => bid_info.price,
+            })
+            .sum()
+    }
+
+    pub fn withdraw(&mut self, timestamp: UnixTimestamp) -> Result<u64, ProgramError> {
+        if self.cancelled {
+            msg!("Auction is cancelled");
+            return Err(VoilaError::InvalidParam.into());
+        }
+        if timestamp < self.end_time {
+            msg!("Auction is not end of bidding yet");
+            return Err(VoilaError::InvalidBidTime.into());
+        }
+        if self.withdrawn {
+            msg!("Auction proceeds are already withdrawn");
+            return Err(VoilaError::InvalidParam.into());
+        }
+        if self.winning_bids.is_empty() {
             msg!("No bidder exists");
-            Err(VoilaError::NFTCannotClaim.into())
+            return Err(VoilaError::InvalidParam.into());
+        }
+        if !self.is_reserve_met() {
+            msg!("Highest bid is below the reserve price");
+            return Err(VoilaError::ReserveNotMet.into());
+        }
+
+        let mut proceeds = 0u64;
+        for (index, bid_info) in self.winning_bids.iter().enumerate() {
+            if !self.is_bid_reserve_met(bid_info) {
+                continue;
+            }
+            // crank rewards are paid out of the proceeds, so they must be known first
+            if self.crank_reward.is_some() && !self.claimed[index] {
+                msg!("Winning bid at index {} is not settled yet", index);
+                return Err(VoilaError::InvalidParam.into());
+            }
+            proceeds = proceeds.checked_add(bid_info.price).ok_or(VoilaError::MathOverflow)?;
+        }
+        let crank_rewards = self.crank_reward
+            .unwrap_or(0)
+            .checked_mul(self.cranked as u64)
+            .ok_or(VoilaError::MathOverflow)?;
+        self.withdrawn = true;
+
+        let proceeds = proceeds.checked_sub(crank_rewards).ok_or(VoilaError::MathOverflow)?;
+        Ok(proceeds)
+    }
+}
+
+impl Updater<NFTAuctionUpdate> for NFTAuction {
+    fn can_update(&self, param: &NFTAuctionUpdate) -> bool {
+        if self.bid_count > 0 {
+            msg!("NFT auction can not be updated after the first bid");
+            return false;
+        }
+        if self.cancelled {
+            msg!("NFT auction is cancelled");
+            return false;
+        }
+
+        let start_time = param.start_time.unwrap_or(self.start_time);
+        let end_time = param.end_time.unwrap_or(self.end_time);
+        let base_price = param.base_price.unwrap_or(self.base_price);
+
+        start_time < end_time
+            && param.min_raise_bps.is_none_or(|min_raise_bps| min_raise_bps <= MAX_MIN_RAISE_BPS)
+            && self.bid_extension.is_none_or(|bid_extension| bid_extension.is_valid(end_time))
+            && self.reserve_price.is_none_or(|reserve_price| reserve_price.price >= base_price)
+            && self.buy_now_price.is_none_or(|buy_now_price| buy_now_price > base_price)
+            && self.crank_reward.is_none_or(|crank_reward| crank_reward <= base_price)
+            && param.uri.as_ref().is_none_or(|uri| is_valid_metadata(&self.name, uri, self.winners as u16 - 1))
+    }
+
+    fn update_unchecked(&mut self, param: NFTAuctionUpdate) {
+        if let Some(start_time) = param.start_time {
+            self.start_time = start_time;
+        }
+        if let Some(end_time) = param.end_time {
+            self.end_time = end_time;
+        }
+        if let Some(base_price) = param.base_price {
+            self.base_price = base_price;
+        }
+        if let Some(min_raise_price) = param.min_raise_price {
+            self.min_raise_price = min_raise_price;
+        }
+        if let Some(min_raise_bps) = param.min_raise_bps {
+            self.min_raise_bps = min_raise_bps;
+        }
+        if let Some(uri) = param.uri {
+            self.uri = uri;
         }
     }
 }
@@ -140,5 +691,356 @@ impl IsInitialized for NFTAuction {
 }
 
 impl Packer for NFTAuction {
+    const LEN: usize = 2048;
+}
+
+/// Layout of single winner auctions created before multi-winner auctions, kept to migrate them
+#[derive(Clone, BorshSerialize, BorshDeserialize)]
+pub struct LegacyNFTAuction {
+    pub is_initialized: bool,
+    pub admin: Pubkey,
+    pub pda_authority: Pubkey,
+    pub pda_seed: [u8; 1],
+    pub start_time: UnixTimestamp,
+    pub end_time: UnixTimestamp,
+    pub base_price: u64,
+    pub min_raise_price: u64,
+    pub current_bid_info: Option<BidInfo>,
+    pub previous_bid_infos: Vec<BidInfo>,
+    pub claimed: bool,
+    pub name: String,
+    pub uri: String,
+}
+
+impl IsInitialized for LegacyNFTAuction {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Packer for LegacyNFTAuction {
     const LEN: usize = 512;
-}
\ No newline at end of file
+}
+
+impl From<LegacyNFTAuction> for NFTAuction {
+    /// Proceeds of legacy auctions are paid to the admin
+    fn from(legacy: LegacyNFTAuction) -> Self {
+        Self {
+            is_initialized: legacy.is_initialized,
+            admin: legacy.admin,
+            receipt: legacy.admin,
+            pda_authority: legacy.pda_authority,
+            pda_seed: legacy.pda_seed,
+            start_time: legacy.start_time,
+            end_time: legacy.end_time,
+            base_price: legacy.base_price,
+            min_raise_price: legacy.min_raise_price,
+            min_raise_bps: 0,
+            winners: 1,
+            bid_extension: None,
+            reserve_price: None,
+            buy_now_price: None,
+            payment_mint: None,
+            consignment: None,
+            crank_reward: None,
+            cranked: 0,
+            key_gate: None,
+            royalty: None,
+            collection: None,
+            proxy_max_price: None,
+            winning_bids: legacy.current_bid_info.into_iter().collect(),
+            previous_bid_infos: legacy.previous_bid_infos,
+            bid_count: 0,
+            claimed: vec![legacy.claimed],
+            withdrawn: false,
+            cancelled: false,
+            name: legacy.name,
+            uri: legacy.uri,
+        }
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct BidEscrow {
+    pub is_initialized: bool,
+    pub nft_auction: Pubkey,
+    pub bidder: Pubkey,
+    pub payment_mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+impl BidEscrow {
+    pub fn new(nft_auction: Pubkey, bidder: Pubkey, payment_mint: Option<Pubkey>) -> Self {
+        Self {
+            is_initialized: true,
+            nft_auction,
+            bidder,
+            payment_mint,
+            amount: 0,
+        }
+    }
+
+    pub fn deposit(&mut self, amount: u64) -> ProgramResult {
+        self.amount = self.amount.checked_add(amount).ok_or(VoilaError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn refund(&mut self, locked_amount: u64) -> Result<u64, ProgramError> {
+        let refund_amount = self.amount.checked_sub(locked_amount).ok_or(VoilaError::MathOverflow)?;
+        if refund_amount == 0 {
+            msg!("No refundable amount in bid escrow");
+            return Err(VoilaError::InvalidParam.into());
+        }
+        self.amount = locked_amount;
+
+        Ok(refund_amount)
+    }
+}
+
+impl IsInitialized for BidEscrow {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Packer for BidEscrow {
+    const LEN: usize = 1 + 32 + 32 + 33 + 8;
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct BidHistoryPage {
+    pub is_initialized: bool,
+    pub nft_auction: Pubkey,
+    pub page: u32,
+    pub bid_infos: Vec<BidInfo>,
+}
+
+impl BidHistoryPage {
+    pub fn new(nft_auction: Pubkey, page: u32) -> Self {
+        Self {
+            is_initialized: true,
+            nft_auction,
+            page,
+            bid_infos: Vec::with_capacity(BID_HISTORY_PAGE_LEN),
+        }
+    }
+
+    pub fn push(&mut self, bid_info: BidInfo) -> ProgramResult {
+        if self.bid_infos.len() >= BID_HISTORY_PAGE_LEN {
+            msg!("Bid history page is full");
+            return Err(VoilaError::InvalidParam.into());
+        }
+        self.bid_infos.push(bid_info);
+
+        Ok(())
+    }
+}
+
+impl IsInitialized for BidHistoryPage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Packer for BidHistoryPage {
+    const LEN: usize = 1024;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_PRICE: u64 = 100;
+    const MIN_RAISE_PRICE: u64 = 10;
+
+    fn single_winner_auction() -> NFTAuction {
+        NFTAuction::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &crate::ID,
+            0,
+            1000,
+            BASE_PRICE,
+            MIN_RAISE_PRICE,
+            0,
+            1,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            "auction".to_string(),
+            "https://voila.test/auction".to_string(),
+        )
+    }
+
+    fn leader(auction: &NFTAuction) -> (Pubkey, u64) {
+        let bid_info = auction.highest_bid().unwrap();
+        (bid_info.bidder, bid_info.price)
+    }
+
+    #[test]
+    fn test_proxy_outbid_by_direct_bid() {
+        let mut auction = single_winner_auction();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let (bid_info, _) = auction.bid_with_max(500, 1, alice).unwrap();
+        assert_eq!(bid_info.price, BASE_PRICE + MIN_RAISE_PRICE);
+        assert_eq!(auction.proxy_max_price, Some(500));
+
+        // a direct bid under the maximum raises the proxy leader just above it
+        let (bid_info, displaced_bid_info) = auction.bid(100, 2, bob).unwrap();
+        assert_eq!((bid_info.bidder, bid_info.price), (bob, 210));
+        assert!(displaced_bid_info.is_none());
+        assert_eq!(leader(&auction), (alice, 220));
+        assert_eq!(auction.proxy_max_price, Some(500));
+        let raised_leader_bid_info = auction.raised_leader_bid(&bob, Some(110), 2).unwrap();
+        assert_eq!((raised_leader_bid_info.bidder, raised_leader_bid_info.price), (alice, 220));
+
+        // a direct bid over the maximum takes the lead
+        let (bid_info, displaced_bid_info) = auction.bid(400, 3, bob).unwrap();
+        assert_eq!((bid_info.bidder, bid_info.price), (bob, 620));
+        assert_eq!(displaced_bid_info.unwrap().bidder, alice);
+        assert_eq!(leader(&auction), (bob, 620));
+        assert_eq!(auction.proxy_max_price, None);
+        assert!(auction.raised_leader_bid(&bob, Some(220), 3).is_none());
+    }
+
+    #[test]
+    fn test_two_proxies_compete() {
+        let mut auction = single_winner_auction();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        auction.bid_with_max(500, 1, alice).unwrap();
+
+        // lower maximum loses, the leader is raised by one increment over it
+        let (bid_info, displaced_bid_info) = auction.bid_with_max(300, 2, bob).unwrap();
+        assert_eq!((bid_info.bidder, bid_info.price), (bob, 300));
+        assert!(displaced_bid_info.is_none());
+        assert_eq!(leader(&auction), (alice, 310));
+        assert_eq!(auction.proxy_max_price, Some(500));
+
+        // higher maximum wins at one increment over the previous maximum
+        let (bid_info, displaced_bid_info) = auction.bid_with_max(600, 3, bob).unwrap();
+        assert_eq!((bid_info.bidder, bid_info.price), (bob, 510));
+        assert_eq!(displaced_bid_info.unwrap().bidder, alice);
+        assert_eq!(leader(&auction), (bob, 510));
+        assert_eq!(auction.proxy_max_price, Some(600));
+    }
+
+    #[test]
+    fn test_max_below_min_increment() {
+        let mut auction = single_winner_auction();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(
+            auction.bid_with_max(BASE_PRICE + MIN_RAISE_PRICE - 1, 1, alice).err(),
+            Some(VoilaError::InvalidBidPrice.into()),
+        );
+
+        auction.bid_with_max(BASE_PRICE + MIN_RAISE_PRICE, 1, alice).unwrap();
+        assert_eq!(auction.proxy_max_price, None);
+        assert_eq!(
+            auction.bid_with_max(BASE_PRICE + 2 * MIN_RAISE_PRICE - 1, 2, bob).err(),
+            Some(VoilaError::InvalidBidPrice.into()),
+        );
+        assert_eq!(leader(&auction), (alice, BASE_PRICE + MIN_RAISE_PRICE));
+    }
+
+    #[test]
+    fn test_leader_raises_own_max() {
+        let mut auction = single_winner_auction();
+        let alice = Pubkey::new_unique();
+
+        auction.bid_with_max(500, 1, alice).unwrap();
+
+        // visible bid stays, only the maximum is raised
+        let (bid_info, displaced_bid_info) = auction.bid_with_max(800, 2, alice).unwrap();
+        assert_eq!((bid_info.bidder, bid_info.price), (alice, BASE_PRICE + MIN_RAISE_PRICE));
+        assert!(displaced_bid_info.is_none());
+        assert_eq!(leader(&auction), (alice, BASE_PRICE + MIN_RAISE_PRICE));
+        assert_eq!(auction.proxy_max_price, Some(800));
+
+        assert_eq!(
+            auction.bid_with_max(700, 3, alice).err(),
+            Some(VoilaError::InvalidBidPrice.into()),
+        );
+        assert_eq!(auction.proxy_max_price, Some(800));
+    }
+
+    #[test]
+    fn test_leader_direct_bid_under_own_max() {
+        let mut auction = single_winner_auction();
+        let alice = Pubkey::new_unique();
+
+        auction.bid_with_max(500, 1, alice).unwrap();
+
+        // the leader is not raised against its own bid
+        let (bid_info, displaced_bid_info) = auction.bid(20, 2, alice).unwrap();
+        assert_eq!((bid_info.bidder, bid_info.price), (alice, 130));
+        assert!(displaced_bid_info.is_none());
+        assert_eq!(leader(&auction), (alice, 130));
+        assert_eq!(auction.proxy_max_price, Some(500));
+        assert!(auction.previous_bid_infos.is_empty());
+
+        // a direct bid over the own maximum drops it
+        auction.bid(400, 3, alice).unwrap();
+        assert_eq!(leader(&auction), (alice, 530));
+        assert_eq!(auction.proxy_max_price, None);
+    }
+
+    #[test]
+    fn test_cancelled_auction_refunds_all() {
+        let mut auction = single_winner_auction();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        auction.bid_with_max(500, 1, alice).unwrap();
+        auction.cancel(2).unwrap();
+
+        assert_eq!(auction.locked_amount(2, &alice), 0);
+        assert_eq!(auction.bid(100, 3, bob).err(), Some(VoilaError::InvalidBidTime.into()));
+        assert_eq!(auction.settle(1000, 0).err(), Some(VoilaError::NFTCannotClaim.into()));
+        assert_eq!(auction.withdraw(1000).err(), Some(VoilaError::InvalidParam.into()));
+        assert_eq!(auction.cancel(3).err(), Some(VoilaError::InvalidParam.into()));
+    }
+
+    #[test]
+    fn test_migrate_legacy_auction() {
+        let (admin, alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let legacy = LegacyNFTAuction {
+            is_initialized: true,
+            admin,
+            pda_authority: Pubkey::new_unique(),
+            pda_seed: [255],
+            start_time: 0,
+            end_time: 1000,
+            base_price: BASE_PRICE,
+            min_raise_price: MIN_RAISE_PRICE,
+            current_bid_info: Some(BidInfo { bidder: alice, price: 150, timestamp: 20 }),
+            previous_bid_infos: vec![BidInfo { bidder: bob, price: 120, timestamp: 10 }],
+            claimed: false,
+            name: "auction".to_string(),
+            uri: "https://voila.test/auction".to_string(),
+        };
+        let mut data = vec![0; LegacyNFTAuction::LEN];
+        legacy.pack(&mut data).unwrap();
+        assert!(NFTAuction::unpack(&data).is_err());
+
+        let mut auction: NFTAuction = LegacyNFTAuction::unpack(&data).unwrap().into();
+        assert_eq!(auction.receipt, admin);
+        assert_eq!(auction.previous_bid_infos.len(), 1);
+        assert_eq!(auction.locked_amount(500, &alice), 150);
+
+        auction.bid(MIN_RAISE_PRICE, 500, bob).unwrap();
+        assert_eq!(auction.winning_bids[0].price, 160);
+        assert_eq!(auction.locked_amount(500, &alice), 0);
+
+        auction.claim(1000, &bob, 0).unwrap();
+        assert_eq!(auction.withdraw(1000).unwrap(), 160);
+    }
+}
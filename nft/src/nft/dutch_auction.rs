@@ -0,0 +1,131 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use serde::{Serialize, Deserialize};
+use solana_program::{msg, pubkey::Pubkey, clock::UnixTimestamp, program_error::ProgramError, program_pack::IsInitialized};
+
+use crate::{pda::get_nft_auction_authority_pda, error::VoilaError, Packer};
+
+use super::auction::BidInfo;
+
+#[derive(Clone, Copy, Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub enum PriceDecay {
+    Linear,
+    /// Price drops once every given number of seconds
+    Stepped(u32),
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct DutchAuction {
+    pub is_initialized: bool,
+    pub admin: Pubkey,
+    pub receipt: Pubkey,
+    pub pda_authority: Pubkey,
+    pub pda_seed: [u8; 1],
+    pub start_time: UnixTimestamp,
+    pub end_time: UnixTimestamp,
+    pub start_price: u64,
+    pub floor_price: u64,
+    pub price_decay: PriceDecay,
+    pub winner: Option<BidInfo>,
+    pub name: String,
+    pub uri: String,
+}
+
+impl DutchAuction {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        admin: Pubkey,
+        receipt: Pubkey,
+        dutch_auction: &Pubkey,
+        program_id: &Pubkey,
+        start_time: UnixTimestamp,
+        end_time: UnixTimestamp,
+        start_price: u64,
+        floor_price: u64,
+        price_decay: PriceDecay,
+        name: String,
+        uri: String,
+    ) -> Self {
+        let (pda_authority, _, pda_seed)
+            = get_nft_auction_authority_pda(dutch_auction, program_id);
+
+        Self {
+            is_initialized: true,
+            admin,
+            receipt,
+            pda_authority,
+            pda_seed,
+            start_time,
+            end_time,
+            start_price,
+            floor_price,
+            price_decay,
+            winner: None,
+            name,
+            uri,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.start_time < self.end_time
+            && self.start_price >= self.floor_price
+            && self.price_decay != PriceDecay::Stepped(0)
+    }
+
+    #[inline]
+    pub fn authority_signer_seeds<'a>(&'a self, dutch_auction: &'a Pubkey) -> [&'a [u8]; 2] {
+        [dutch_auction.as_ref(), &self.pda_seed]
+    }
+
+    pub fn current_price(&self, timestamp: UnixTimestamp) -> u64 {
+        if timestamp <= self.start_time {
+            return self.start_price;
+        } else if timestamp >= self.end_time {
+            return self.floor_price;
+        }
+
+        let duration = (self.end_time - self.start_time) as u128;
+        let mut elapsed = (timestamp - self.start_time) as u128;
+        if let PriceDecay::Stepped(interval) = self.price_decay {
+            elapsed -= elapsed % interval.max(1) as u128;
+        }
+
+        let price_range = (self.start_price - self.floor_price) as u128;
+        let decayed_price = price_range * elapsed / duration;
+
+        self.start_price - decayed_price as u64
+    }
+
+    pub fn buy(&mut self, timestamp: UnixTimestamp, buyer: Pubkey) -> Result<u64, ProgramError> {
+        if timestamp < self.start_time {
+            msg!("Dutch auction is not started yet");
+            return Err(VoilaError::InvalidBidTime.into());
+        }
+        if timestamp >= self.end_time {
+            msg!("Dutch auction is ended");
+            return Err(VoilaError::InvalidBidTime.into());
+        }
+        if self.winner.is_some() {
+            msg!("Dutch auction is already sold");
+            return Err(VoilaError::NFTEndOfSale.into());
+        }
+
+        let price = self.current_price(timestamp);
+        self.winner = Some(BidInfo {
+            bidder: buyer,
+            price,
+            timestamp,
+        });
+
+        Ok(price)
+    }
+}
+
+impl IsInitialized for DutchAuction {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Packer for DutchAuction {
+    const LEN: usize = 512;
+}
@@ -1,39 +1,149 @@
-use metaplex_token_metadata::state::Data;
-use metaplex_token_metadata::instruction::{create_metadata_accounts, create_master_edition};
+use mpl_token_metadata::state::{DataV2, Creator, Collection};
+use mpl_token_metadata::instruction::{
+    create_metadata_accounts_v3,
+    create_master_edition_v3,
+    sign_metadata,
+    verify_collection,
+};
 use solana_program::{entrypoint::ProgramResult, account_info::AccountInfo, pubkey::Pubkey};
 
 use crate::invoker::invoke_optionally_signed;
 
 use super::auction::NFTAuction;
-use super::{Meta, CommonNFTInfo};
+use super::dutch_auction::DutchAuction;
+use super::sealed_auction::SealedAuction;
+use super::{Meta, CommonNFTInfo, Royalty, token_name, token_uri, collection_uri};
 
-impl Meta<Data> for CommonNFTInfo {
-    fn metadata(&self, _mint: &Pubkey) -> Data {
-        let name = format!("{}", self.name);
-        // let uri = format!("{}/{}", self.uri, mint.to_string());
-        let uri = "http://3.0.95.230/nft/3zhd2GY6Yx9VcY8krE2kc9heJFLdCcKdLV6BmcGLs9zn.png".to_string();
+/// Seller fee and creators of metadata, the PDA authority is verified afterwards by `sign_metadata`
+fn royalty_creators(royalty: Option<&Royalty>, pda_authority: &Pubkey) -> (u16, Option<Vec<Creator>>) {
+    match royalty {
+        Some(royalty) => {
+            let creators = std::iter::once(Creator {
+                address: *pda_authority,
+                verified: false,
+                share: 0,
+            })
+            .chain(royalty.creators.iter().map(|creator| Creator {
+                address: creator.address,
+                verified: false,
+                share: creator.share,
+            }))
+            .collect();
 
-        Data {
-            name,
+            (royalty.seller_fee_basis_points, Some(creators))
+        }
+        None => (0, None),
+    }
+}
+
+impl Meta<DataV2> for CommonNFTInfo {
+    fn metadata(&self, id: u16) -> DataV2 {
+        let (seller_fee_basis_points, creators) = royalty_creators(self.royalty.as_ref(), &self.pda_authority);
+
+        DataV2 {
+            name: token_name(&self.name, id),
+            symbol: "VNFT".to_string(),
+            uri: token_uri(&self.uri, id),
+            seller_fee_basis_points,
+            creators,
+            collection: self.collection.map(|key| Collection { verified: false, key }),
+            uses: None,
+        }
+    }
+
+    fn collection_metadata(&self) -> DataV2 {
+        let (seller_fee_basis_points, creators) = royalty_creators(self.royalty.as_ref(), &self.pda_authority);
+
+        DataV2 {
+            name: self.name.clone(),
+            symbol: "VNFT".to_string(),
+            uri: collection_uri(&self.uri),
+            seller_fee_basis_points,
+            creators,
+            collection: None,
+            uses: None,
+        }
+    }
+}
+
+impl Meta<DataV2> for NFTAuction {
+    fn metadata(&self, id: u16) -> DataV2 {
+        let (seller_fee_basis_points, creators) = royalty_creators(self.royalty.as_ref(), &self.pda_authority);
+
+        DataV2 {
+            name: token_name(&self.name, id),
+            symbol: "VNFT".to_string(),
+            uri: token_uri(&self.uri, id),
+            seller_fee_basis_points,
+            creators,
+            collection: self.collection.map(|key| Collection { verified: false, key }),
+            uses: None,
+        }
+    }
+
+    fn collection_metadata(&self) -> DataV2 {
+        let (seller_fee_basis_points, creators) = royalty_creators(self.royalty.as_ref(), &self.pda_authority);
+
+        DataV2 {
+            name: self.name.clone(),
+            symbol: "VNFT".to_string(),
+            uri: collection_uri(&self.uri),
+            seller_fee_basis_points,
+            creators,
+            collection: None,
+            uses: None,
+        }
+    }
+}
+
+impl Meta<DataV2> for DutchAuction {
+    fn metadata(&self, id: u16) -> DataV2 {
+        DataV2 {
+            name: token_name(&self.name, id),
             symbol: "VNFT".to_string(),
-            uri,
+            uri: token_uri(&self.uri, id),
             seller_fee_basis_points: 0,
             creators: None,
+            collection: None,
+            uses: None,
+        }
+    }
+
+    fn collection_metadata(&self) -> DataV2 {
+        DataV2 {
+            name: self.name.clone(),
+            symbol: "VNFT".to_string(),
+            uri: collection_uri(&self.uri),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
         }
     }
 }
 
-impl Meta<Data> for NFTAuction {
-    fn metadata(&self, _mint: &Pubkey) -> Data {
-        let name = format!("{}", self.name);
-        let uri = "http://3.0.95.230/nft/3zhd2GY6Yx9VcY8krE2kc9heJFLdCcKdLV6BmcGLs9zn.png".to_string();
+impl Meta<DataV2> for SealedAuction {
+    fn metadata(&self, id: u16) -> DataV2 {
+        DataV2 {
+            name: token_name(&self.name, id),
+            symbol: "VNFT".to_string(),
+            uri: token_uri(&self.uri, id),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        }
+    }
 
-        Data {
-            name,
+    fn collection_metadata(&self) -> DataV2 {
+        DataV2 {
+            name: self.name.clone(),
             symbol: "VNFT".to_string(),
-            uri,
+            uri: collection_uri(&self.uri),
             seller_fee_basis_points: 0,
             creators: None,
+            collection: None,
+            uses: None,
         }
     }
 }
@@ -75,34 +185,38 @@ pub fn process_invoke_metaplex_create_metadata_accounts<'a>(
     metadata_account: &AccountInfo<'a>,
     mint_account: &AccountInfo<'a>,
     mint_authority: &AccountInfo<'a>,
-    user_authority: &AccountInfo<'a>,
+    update_authority: &AccountInfo<'a>,
+    payer_authority: &AccountInfo<'a>,
     system_program: &AccountInfo<'a>,
     rent_account: &AccountInfo<'a>,
-    data: Data,
+    data: DataV2,
     signer_seeds: &[&[u8]],
 ) -> ProgramResult {
     invoke_optionally_signed(
-        &create_metadata_accounts(
+        &create_metadata_accounts_v3(
             *program_account.key,
             *metadata_account.key,
             *mint_account.key,
             *mint_authority.key,
-            *user_authority.key,
-            *user_authority.key,
+            *payer_authority.key,
+            *update_authority.key,
             data.name,
             data.symbol,
             data.uri,
             data.creators,
             data.seller_fee_basis_points,
-            false,
             true,
+            true,
+            data.collection,
+            data.uses,
+            None,
         ),
         &[
             metadata_account.clone(),
             mint_account.clone(),
             mint_authority.clone(),
-            user_authority.clone(),
-            user_authority.clone(),
+            payer_authority.clone(),
+            update_authority.clone(),
             system_program.clone(),
             rent_account.clone(),
             program_account.clone(),
@@ -118,29 +232,31 @@ pub fn process_invoke_metaplex_create_master_edition_accounts<'a>(
     edition_account: &AccountInfo<'a>,
     mint_account: &AccountInfo<'a>,
     mint_authority: &AccountInfo<'a>,
-    user_authority: &AccountInfo<'a>,
+    update_authority: &AccountInfo<'a>,
+    payer_authority: &AccountInfo<'a>,
     token_program: &AccountInfo<'a>,
     system_program: &AccountInfo<'a>,
     rent_account: &AccountInfo<'a>,
+    max_supply: Option<u64>,
     signer_seeds: &[&[u8]],
 ) -> ProgramResult {
     invoke_optionally_signed(
-        &create_master_edition(
+        &create_master_edition_v3(
             *program_account.key,
             *edition_account.key,
             *mint_account.key,
-            *user_authority.key,
+            *update_authority.key,
             *mint_authority.key,
             *metadata_account.key,
-            *user_authority.key,
-            None,
+            *payer_authority.key,
+            max_supply,
         ),
         &[
             edition_account.clone(),
             mint_account.clone(),
-            user_authority.clone(),
+            update_authority.clone(),
             mint_authority.clone(),
-            user_authority.clone(),
+            payer_authority.clone(),
             metadata_account.clone(),
             token_program.clone(),
             system_program.clone(),
@@ -149,4 +265,60 @@ pub fn process_invoke_metaplex_create_master_edition_accounts<'a>(
         ],
         signer_seeds,
     )
-}
\ No newline at end of file
+}
+
+pub fn process_invoke_metaplex_sign_metadata<'a>(
+    program_account: &AccountInfo<'a>,
+    metadata_account: &AccountInfo<'a>,
+    creator: &AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_optionally_signed(
+        &sign_metadata(
+            *program_account.key,
+            *metadata_account.key,
+            *creator.key,
+        ),
+        &[
+            metadata_account.clone(),
+            creator.clone(),
+            program_account.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_invoke_metaplex_verify_collection<'a>(
+    program_account: &AccountInfo<'a>,
+    metadata_account: &AccountInfo<'a>,
+    collection_authority: &AccountInfo<'a>,
+    payer_authority: &AccountInfo<'a>,
+    collection_mint: &AccountInfo<'a>,
+    collection_metadata_account: &AccountInfo<'a>,
+    collection_edition_account: &AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_optionally_signed(
+        &verify_collection(
+            *program_account.key,
+            *metadata_account.key,
+            *collection_authority.key,
+            *payer_authority.key,
+            *collection_mint.key,
+            *collection_metadata_account.key,
+            *collection_edition_account.key,
+            None,
+        ),
+        &[
+            metadata_account.clone(),
+            collection_authority.clone(),
+            payer_authority.clone(),
+            collection_mint.clone(),
+            collection_metadata_account.clone(),
+            collection_edition_account.clone(),
+            program_account.clone(),
+        ],
+        signer_seeds,
+    )
+}
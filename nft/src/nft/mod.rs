@@ -1,18 +1,100 @@
 #[cfg(feature = "metaplex")]
 pub mod metaplex;
+pub mod allowlist;
 pub mod auction;
+pub mod dutch_auction;
+pub mod sealed_auction;
 
 use borsh::{BorshSerialize, BorshDeserialize};
 use serde::{Serialize, Deserialize};
 use solana_program::{
+    msg,
     pubkey::Pubkey,
+    clock::UnixTimestamp,
+    program_error::ProgramError,
     program_pack::IsInitialized,
+    entrypoint::ProgramResult,
 };
 
-use crate::{Packer, pda::get_common_nft_authority_pda};
+use crate::{Packer, error::VoilaError, pda::get_common_nft_authority_pda};
+
+use self::allowlist::AllowlistProof;
+
+/// Length limits of Metaplex metadata
+pub const MAX_NAME_LENGTH: usize = 32;
+pub const MAX_URI_LENGTH: usize = 200;
+pub const MAX_CREATOR_LIMIT: usize = 5;
+pub const MAX_SELLER_FEE_BASIS_POINTS: u16 = 10000;
 
 pub trait Meta<Data: Sized> {
-    fn metadata(&self, mint: &Pubkey) -> Data;
+    fn metadata(&self, id: u16) -> Data;
+
+    fn collection_metadata(&self) -> Data;
+}
+
+#[inline]
+pub fn token_name(name: &str, id: u16) -> String {
+    format!("{} #{}", name, id)
+}
+
+#[inline]
+pub fn token_uri(uri: &str, id: u16) -> String {
+    format!("{}/{}.json", uri.trim_end_matches('/'), id)
+}
+
+#[inline]
+pub fn collection_uri(uri: &str) -> String {
+    format!("{}/collection.json", uri.trim_end_matches('/'))
+}
+
+/// Checks that the name and uri of every token up to `max_id` and of the collection fit in Metaplex metadata
+pub fn is_valid_metadata(name: &str, uri: &str, max_id: u16) -> bool {
+    token_name(name, max_id).len() <= MAX_NAME_LENGTH
+        && token_uri(uri, max_id).len() <= MAX_URI_LENGTH
+        && collection_uri(uri).len() <= MAX_URI_LENGTH
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub share: u8,
+}
+
+/// Secondary sale royalty of minted NFTs, split among `creators` by share.
+/// The PDA authority joins as an extra creator with zero share to prove the origin.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Royalty {
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<Creator>,
+}
+
+impl Royalty {
+    pub fn is_valid(&self, pda_authority: &Pubkey) -> bool {
+        self.seller_fee_basis_points <= MAX_SELLER_FEE_BASIS_POINTS
+            && !self.creators.is_empty()
+            && self.creators.len() < MAX_CREATOR_LIMIT
+            && self.creators.iter().map(|creator| creator.share as u16).sum::<u16>() == 100
+            && self.creators.iter().enumerate().all(|(i, creator)| {
+                &creator.address != pda_authority
+                    && self.creators[..i].iter().all(|other| other.address != creator.address)
+            })
+    }
+}
+
+/// Presale window before the public sale, open only to holders of a `UserKeyRecord` of `key_info`.
+#[derive(Clone, Copy, Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Presale {
+    pub key_info: Pubkey,
+    pub start_time: UnixTimestamp,
+    pub end_time: UnixTimestamp,
+    /// Discounted price in presale, the public price if `None`
+    pub price: Option<u64>,
+}
+
+impl Presale {
+    pub fn is_valid(&self) -> bool {
+        self.start_time < self.end_time
+    }
 }
 
 #[derive(Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
@@ -25,6 +107,20 @@ pub struct CommonNFTInfo {
     pub price: u64,
     pub max_amount: u16,
     pub current_amount: u16,
+    /// Public sale opens when presale ends
+    pub presale: Option<Presale>,
+    /// Public sale opens no earlier than `sale_start`
+    pub sale_start: Option<UnixTimestamp>,
+    /// No purchase is accepted from `sale_end` on, including presale
+    pub sale_end: Option<UnixTimestamp>,
+    pub max_per_wallet: Option<u16>,
+    /// Priced in this SPL token instead of lamports if set
+    pub payment_mint: Option<Pubkey>,
+    /// Merkle root of allowlisted wallets, only they can purchase if set
+    pub allowlist_root: Option<[u8; 32]>,
+    pub royalty: Option<Royalty>,
+    /// Mint of the Metaplex collection NFT, set once the collection is created
+    pub collection: Option<Pubkey>,
     pub name: String,
     pub uri: String,
 }
@@ -36,7 +132,7 @@ impl IsInitialized for CommonNFTInfo {
 }
 
 impl Packer for CommonNFTInfo {
-    const LEN: usize = 512;
+    const LEN: usize = 1024;
 }
 
 impl CommonNFTInfo {
@@ -48,6 +144,13 @@ impl CommonNFTInfo {
         program_id: &Pubkey,
         price: u64,
         max_amount: u16,
+        presale: Option<Presale>,
+        sale_start: Option<UnixTimestamp>,
+        sale_end: Option<UnixTimestamp>,
+        max_per_wallet: Option<u16>,
+        payment_mint: Option<Pubkey>,
+        allowlist_root: Option<[u8; 32]>,
+        royalty: Option<Royalty>,
         name: String,
         uri: String,
     ) -> Self {
@@ -63,6 +166,14 @@ impl CommonNFTInfo {
             price,
             max_amount,
             current_amount: 0,
+            presale,
+            sale_start,
+            sale_end,
+            max_per_wallet,
+            payment_mint,
+            allowlist_root,
+            royalty,
+            collection: None,
             name,
             uri,
         }
@@ -72,4 +183,106 @@ impl CommonNFTInfo {
     pub fn authority_signer_seeds<'a>(&'a self, nft_pubkey: &'a Pubkey) -> [&'a [u8]; 2] {
         [nft_pubkey.as_ref(), &self.pda_seed]
     }
+
+    pub fn is_valid_sale_window(&self) -> bool {
+        let opens_at = match (&self.presale, self.sale_start) {
+            (Some(presale), _) => Some(presale.start_time),
+            (None, sale_start) => sale_start,
+        };
+        match (opens_at, self.sale_end) {
+            (Some(opens_at), Some(sale_end)) => opens_at < sale_end,
+            _ => true,
+        }
+    }
+
+    /// Verifies the buyer against the allowlist, returns the purchase limit of the buyer
+    pub fn wallet_limit(&self, buyer: &Pubkey, proof: Option<&AllowlistProof>) -> Result<Option<u16>, ProgramError> {
+        let allowance = match (&self.allowlist_root, proof) {
+            (None, _) => None,
+            (Some(_), None) => {
+                msg!("Allowlist proof is required");
+                return Err(VoilaError::NotInAllowlist.into());
+            }
+            (Some(root), Some(proof)) => {
+                if !proof.verify(root, buyer) {
+                    msg!("Buyer {} is not in the allowlist", buyer);
+                    return Err(VoilaError::NotInAllowlist.into());
+                }
+                Some(proof.allowance).filter(|allowance| *allowance > 0)
+            }
+        };
+
+        Ok(match (self.max_per_wallet, allowance) {
+            (Some(max_per_wallet), Some(allowance)) => Some(max_per_wallet.min(allowance)),
+            (max_per_wallet, allowance) => max_per_wallet.or(allowance),
+        })
+    }
+
+    /// Returns the price at `timestamp` and the key info the buyer must hold, if any
+    pub fn purchase_terms(&self, timestamp: UnixTimestamp) -> Result<(u64, Option<&Pubkey>), ProgramError> {
+        if let Some(sale_end) = self.sale_end {
+            if timestamp >= sale_end {
+                msg!("Sale of common NFT is ended at {}", sale_end);
+                return Err(VoilaError::SaleEnded.into());
+            }
+        }
+
+        match &self.presale {
+            Some(presale) if timestamp < presale.start_time => {
+                msg!("Presale of common NFT is not started yet");
+                Err(VoilaError::SaleNotStarted.into())
+            }
+            Some(presale) if timestamp < presale.end_time => {
+                Ok((presale.price.unwrap_or(self.price), Some(&presale.key_info)))
+            }
+            _ => match self.sale_start {
+                Some(sale_start) if timestamp < sale_start => {
+                    msg!("Public sale of common NFT is not started until {}", sale_start);
+                    Err(VoilaError::SaleNotStarted.into())
+                }
+                _ => Ok((self.price, None)),
+            },
+        }
+    }
+}
+
+/// Number of NFTs a buyer has purchased from a common NFT drop
+#[derive(Debug, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct PurchaseRecord {
+    pub is_initialized: bool,
+    pub common_nft: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u16,
+}
+
+impl IsInitialized for PurchaseRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Packer for PurchaseRecord {
+    const LEN: usize = 1 + 32 + 32 + 2;
+}
+
+impl PurchaseRecord {
+    pub fn new(common_nft: Pubkey, buyer: Pubkey) -> Self {
+        Self {
+            is_initialized: true,
+            common_nft,
+            buyer,
+            amount: 0,
+        }
+    }
+
+    pub fn purchase(&mut self, count: u16, max_per_wallet: u16) -> ProgramResult {
+        let amount = self.amount.checked_add(count).ok_or(VoilaError::MathOverflow)?;
+        if amount > max_per_wallet {
+            msg!("Buyer has purchased {}, max per wallet is {}", self.amount, max_per_wallet);
+            return Err(VoilaError::WalletLimitExceeded.into());
+        }
+        self.amount = amount;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,271 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use serde::{Serialize, Deserialize};
+use solana_program::{
+    msg,
+    hash::hashv,
+    pubkey::Pubkey,
+    clock::UnixTimestamp,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
+};
+
+use crate::{pda::get_nft_auction_authority_pda, error::VoilaError, Packer};
+
+use super::auction::BidInfo;
+
+const MAX_BASIS_POINTS: u16 = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub enum SealedAuctionKind {
+    /// Winner pays the highest revealed bid
+    FirstPrice,
+    /// Winner pays the second highest revealed bid (Vickrey)
+    SecondPrice,
+}
+
+#[inline]
+pub fn sealed_bid_commitment(price: u64, salt: &[u8; 32], bidder: &Pubkey) -> [u8; 32] {
+    hashv(&[&price.to_le_bytes(), salt, bidder.as_ref()]).to_bytes()
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct SealedAuction {
+    pub is_initialized: bool,
+    pub admin: Pubkey,
+    pub receipt: Pubkey,
+    pub pda_authority: Pubkey,
+    pub pda_seed: [u8; 1],
+    pub kind: SealedAuctionKind,
+    pub start_time: UnixTimestamp,
+    pub commit_end_time: UnixTimestamp,
+    pub reveal_end_time: UnixTimestamp,
+    pub min_price: u64,
+    /// Basis points of the deposit forfeited to receipt by bidders who never reveal
+    pub slash_bps: u16,
+    pub commit_count: u32,
+    pub highest_bid_info: Option<BidInfo>,
+    pub second_price: u64,
+    pub settled: bool,
+    pub name: String,
+    pub uri: String,
+}
+
+impl SealedAuction {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        admin: Pubkey,
+        receipt: Pubkey,
+        sealed_auction: &Pubkey,
+        program_id: &Pubkey,
+        kind: SealedAuctionKind,
+        start_time: UnixTimestamp,
+        commit_end_time: UnixTimestamp,
+        reveal_end_time: UnixTimestamp,
+        min_price: u64,
+        slash_bps: u16,
+        name: String,
+        uri: String,
+    ) -> Self {
+        let (pda_authority, _, pda_seed)
+            = get_nft_auction_authority_pda(sealed_auction, program_id);
+
+        Self {
+            is_initialized: true,
+            admin,
+            receipt,
+            pda_authority,
+            pda_seed,
+            kind,
+            start_time,
+            commit_end_time,
+            reveal_end_time,
+            min_price,
+            slash_bps,
+            commit_count: 0,
+            highest_bid_info: None,
+            second_price: 0,
+            settled: false,
+            name,
+            uri,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.start_time < self.commit_end_time
+            && self.commit_end_time < self.reveal_end_time
+            && self.slash_bps <= MAX_BASIS_POINTS
+    }
+
+    #[inline]
+    pub fn authority_signer_seeds<'a>(&'a self, sealed_auction: &'a Pubkey) -> [&'a [u8]; 2] {
+        [sealed_auction.as_ref(), &self.pda_seed]
+    }
+
+    pub fn commit(&mut self, timestamp: UnixTimestamp, deposit: u64) -> ProgramResult {
+        if timestamp < self.start_time || timestamp >= self.commit_end_time {
+            msg!("Sealed auction is not in commit window");
+            return Err(VoilaError::InvalidBidTime.into());
+        }
+        if deposit < self.min_price {
+            msg!("Sealed bid deposit is lower than min price");
+            return Err(VoilaError::InvalidBidPrice.into());
+        }
+        self.commit_count = self.commit_count.checked_add(1).ok_or(VoilaError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    pub fn reveal(&mut self, timestamp: UnixTimestamp, bidder: Pubkey, price: u64) -> ProgramResult {
+        if timestamp < self.commit_end_time || timestamp >= self.reveal_end_time {
+            msg!("Sealed auction is not in reveal window");
+            return Err(VoilaError::InvalidBidTime.into());
+        }
+        if price < self.min_price {
+            msg!("Revealed price is lower than min price and can not win");
+            return Ok(());
+        }
+
+        let bid_info = BidInfo {
+            bidder,
+            price,
+            timestamp,
+        };
+        match self.highest_bid_info {
+            Some(highest_bid_info) if price > highest_bid_info.price => {
+                self.second_price = highest_bid_info.price;
+                self.highest_bid_info = Some(bid_info);
+            }
+            Some(_) => self.second_price = self.second_price.max(price),
+            None => self.highest_bid_info = Some(bid_info),
+        }
+
+        Ok(())
+    }
+
+    pub fn is_winner(&self, bidder: &Pubkey) -> bool {
+        self.highest_bid_info.is_some_and(|bid_info| &bid_info.bidder == bidder)
+    }
+
+    pub fn clearing_price(&self) -> Option<u64> {
+        self.highest_bid_info.map(|bid_info| match self.kind {
+            SealedAuctionKind::FirstPrice => bid_info.price,
+            SealedAuctionKind::SecondPrice => self.second_price.max(self.min_price),
+        })
+    }
+
+    pub fn settle(&mut self, timestamp: UnixTimestamp, winner: &Pubkey) -> Result<u64, ProgramError> {
+        if timestamp < self.reveal_end_time {
+            msg!("Sealed auction is not end of revealing yet");
+            return Err(VoilaError::InvalidBidTime.into());
+        }
+        if self.settled {
+            msg!("Sealed auction is already settled");
+            return Err(VoilaError::NFTCannotClaim.into());
+        }
+        if !self.is_winner(winner) {
+            msg!("Winner account is not the highest revealed bidder");
+            return Err(VoilaError::NFTCannotClaim.into());
+        }
+        self.settled = true;
+
+        self.clearing_price().ok_or_else(|| VoilaError::NFTCannotClaim.into())
+    }
+}
+
+impl IsInitialized for SealedAuction {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Packer for SealedAuction {
+    const LEN: usize = 512;
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct SealedBid {
+    pub is_initialized: bool,
+    pub sealed_auction: Pubkey,
+    pub bidder: Pubkey,
+    pub commitment: [u8; 32],
+    pub deposit: u64,
+    pub revealed_price: Option<u64>,
+    pub refunded: bool,
+}
+
+impl SealedBid {
+    pub fn new(
+        sealed_auction: Pubkey,
+        bidder: Pubkey,
+        commitment: [u8; 32],
+        deposit: u64,
+    ) -> Self {
+        Self {
+            is_initialized: true,
+            sealed_auction,
+            bidder,
+            commitment,
+            deposit,
+            revealed_price: None,
+            refunded: false,
+        }
+    }
+
+    pub fn reveal(&mut self, price: u64, salt: &[u8; 32]) -> ProgramResult {
+        if self.revealed_price.is_some() {
+            msg!("Sealed bid is already revealed");
+            return Err(VoilaError::InvalidParam.into());
+        }
+        if sealed_bid_commitment(price, salt, &self.bidder) != self.commitment {
+            msg!("Revealed price and salt are not matched with commitment");
+            return Err(VoilaError::InvalidParam.into());
+        }
+        if price > self.deposit {
+            msg!("Revealed price is higher than deposit");
+            return Err(VoilaError::InvalidBidPrice.into());
+        }
+        self.revealed_price = Some(price);
+
+        Ok(())
+    }
+
+    /// Returns the lamports refunded to bidder and slashed to receipt
+    pub fn refund(&mut self, timestamp: UnixTimestamp, sealed_auction: &SealedAuction) -> Result<(u64, u64), ProgramError> {
+        if timestamp < sealed_auction.reveal_end_time {
+            msg!("Sealed auction is not end of revealing yet");
+            return Err(VoilaError::InvalidBidTime.into());
+        }
+        if self.refunded {
+            msg!("Sealed bid is already refunded");
+            return Err(VoilaError::InvalidParam.into());
+        }
+        if sealed_auction.is_winner(&self.bidder) {
+            msg!("Winner deposit is refunded on settlement");
+            return Err(VoilaError::InvalidParam.into());
+        }
+        self.refunded = true;
+
+        if self.revealed_price.is_some() {
+            Ok((self.deposit, 0))
+        } else {
+            let slashed = (self.deposit as u128)
+                .checked_mul(sealed_auction.slash_bps as u128)
+                .ok_or(VoilaError::MathOverflow)?
+                / MAX_BASIS_POINTS as u128;
+            let slashed = slashed as u64;
+
+            Ok((self.deposit - slashed, slashed))
+        }
+    }
+}
+
+impl IsInitialized for SealedBid {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Packer for SealedBid {
+    const LEN: usize = 1 + 32 + 32 + 32 + 8 + 9 + 1;
+}
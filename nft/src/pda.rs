@@ -2,7 +2,14 @@ use solana_program::pubkey::Pubkey;
 
 const KEY_IDENTIFIER: &[u8] = "key".as_bytes();
 const COMMON_NFT_IDENTIFIER: &[u8] = "commonnft".as_bytes();
+const PURCHASE_RECORD_IDENTIFIER: &[u8] = "purchaserecord".as_bytes();
 const NFT_AUCTION_IDENTIFIER: &[u8] = "auction".as_bytes();
+const BID_ESCROW_IDENTIFIER: &[u8] = "bidescrow".as_bytes();
+const BID_HISTORY_IDENTIFIER: &[u8] = "bidhistory".as_bytes();
+const DUTCH_AUCTION_IDENTIFIER: &[u8] = "dutchauction".as_bytes();
+const SEALED_AUCTION_IDENTIFIER: &[u8] = "sealedauction".as_bytes();
+const SEALED_BID_IDENTIFIER: &[u8] = "sealedbid".as_bytes();
+const COLLECTION_MINT_IDENTIFIER: &[u8] = "collection".as_bytes();
 
 #[inline]
 pub fn get_key_info_pda<'a>(
@@ -85,6 +92,23 @@ pub fn get_common_nft_mint_pda<'a>(
     (key, common_nft_ref, id_array, [seed])
 }
 
+#[inline]
+pub fn get_purchase_record_pda<'a>(
+    common_nft: &'a Pubkey,
+    buyer: &'a Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, &'a [u8], &'a [u8], &'a [u8], [u8; 1]) {
+    let common_nft_ref = common_nft.as_ref();
+    let buyer_ref = buyer.as_ref();
+
+    let (key, seed) = Pubkey::find_program_address(
+        &[PURCHASE_RECORD_IDENTIFIER, common_nft_ref, buyer_ref],
+        program_id,
+    );
+
+    (key, PURCHASE_RECORD_IDENTIFIER, common_nft_ref, buyer_ref, [seed])
+}
+
 #[inline]
 pub fn get_nft_auction_pda<'a>(
     admin_authority: &'a Pubkey,
@@ -102,6 +126,91 @@ pub fn get_nft_auction_pda<'a>(
     (key, NFT_AUCTION_IDENTIFIER, admin_authority_ref, sn_array, [seed])
 }
 
+#[inline]
+pub fn get_bid_escrow_pda<'a>(
+    nft_auction: &'a Pubkey,
+    bidder: &'a Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, &'a [u8], &'a [u8], &'a [u8], [u8; 1]) {
+    let nft_auction_ref = nft_auction.as_ref();
+    let bidder_ref = bidder.as_ref();
+
+    let (key, seed) = Pubkey::find_program_address(
+        &[BID_ESCROW_IDENTIFIER, nft_auction_ref, bidder_ref],
+        program_id,
+    );
+
+    (key, BID_ESCROW_IDENTIFIER, nft_auction_ref, bidder_ref, [seed])
+}
+
+#[inline]
+pub fn get_bid_history_page_pda<'a>(
+    nft_auction: &'a Pubkey,
+    page: u32,
+    program_id: &Pubkey,
+) -> (Pubkey, &'a [u8], &'a [u8], [u8; 4], [u8; 1]) {
+    let nft_auction_ref = nft_auction.as_ref();
+    let page_array = page.to_le_bytes();
+
+    let (key, seed) = Pubkey::find_program_address(
+        &[BID_HISTORY_IDENTIFIER, nft_auction_ref, &page_array],
+        program_id,
+    );
+
+    (key, BID_HISTORY_IDENTIFIER, nft_auction_ref, page_array, [seed])
+}
+
+#[inline]
+pub fn get_dutch_auction_pda<'a>(
+    admin_authority: &'a Pubkey,
+    sn: u16,
+    program_id: &Pubkey,
+) -> (Pubkey, &'a [u8], &'a [u8], [u8; 2], [u8; 1]) {
+    let admin_authority_ref = admin_authority.as_ref();
+    let sn_array = sn.to_le_bytes();
+
+    let (key, seed) = Pubkey::find_program_address(
+        &[DUTCH_AUCTION_IDENTIFIER, admin_authority_ref, &sn_array],
+        program_id,
+    );
+
+    (key, DUTCH_AUCTION_IDENTIFIER, admin_authority_ref, sn_array, [seed])
+}
+
+#[inline]
+pub fn get_sealed_auction_pda<'a>(
+    admin_authority: &'a Pubkey,
+    sn: u16,
+    program_id: &Pubkey,
+) -> (Pubkey, &'a [u8], &'a [u8], [u8; 2], [u8; 1]) {
+    let admin_authority_ref = admin_authority.as_ref();
+    let sn_array = sn.to_le_bytes();
+
+    let (key, seed) = Pubkey::find_program_address(
+        &[SEALED_AUCTION_IDENTIFIER, admin_authority_ref, &sn_array],
+        program_id,
+    );
+
+    (key, SEALED_AUCTION_IDENTIFIER, admin_authority_ref, sn_array, [seed])
+}
+
+#[inline]
+pub fn get_sealed_bid_pda<'a>(
+    sealed_auction: &'a Pubkey,
+    bidder: &'a Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, &'a [u8], &'a [u8], &'a [u8], [u8; 1]) {
+    let sealed_auction_ref = sealed_auction.as_ref();
+    let bidder_ref = bidder.as_ref();
+
+    let (key, seed) = Pubkey::find_program_address(
+        &[SEALED_BID_IDENTIFIER, sealed_auction_ref, bidder_ref],
+        program_id,
+    );
+
+    (key, SEALED_BID_IDENTIFIER, sealed_auction_ref, bidder_ref, [seed])
+}
+
 #[inline]
 pub fn get_nft_auction_authority_pda<'a>(
     nft_auction: &'a Pubkey,
@@ -117,17 +226,54 @@ pub fn get_nft_auction_authority_pda<'a>(
     (key, nft_auction_ref, [seed])
 }
 
+/// The first piece keeps the seeds of single winner auctions, so their mints stay where they were
 #[inline]
 pub fn get_auction_nft_mint_pda<'a>(
     authority: &'a Pubkey,
+    index: u8,
     program_id: &Pubkey,
-) -> (Pubkey, &'a [u8], [u8; 1]) {
+) -> (Pubkey, &'a [u8], Vec<u8>, [u8; 1]) {
+    let authority_ref = authority.as_ref();
+    let index_seed = if index == 0 { vec![] } else { vec![index] };
+
+    let (key, seed) = Pubkey::find_program_address(
+        &[authority_ref, &index_seed],
+        program_id,
+    );
+
+    (key, authority_ref, index_seed, [seed])
+}
+
+#[inline]
+pub fn get_collection_mint_pda<'a>(
+    authority: &'a Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, &'static [u8], &'a [u8], [u8; 1]) {
     let authority_ref = authority.as_ref();
 
     let (key, seed) = Pubkey::find_program_address(
-        &[authority_ref],
+        &[COLLECTION_MINT_IDENTIFIER, authority_ref],
         program_id,
     );
 
-    (key, authority_ref, [seed])
+    (key, COLLECTION_MINT_IDENTIFIER, authority_ref, [seed])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_auction_nft_mint_keeps_single_winner_seeds() {
+        let program_id = crate::ID;
+        let authority = Pubkey::new_unique();
+
+        let (key, _, index_seed, seed) = get_auction_nft_mint_pda(&authority, 0, &program_id);
+        assert!(index_seed.is_empty());
+        assert_eq!((key, seed[0]), Pubkey::find_program_address(&[authority.as_ref()], &program_id));
+
+        let (key, _, index_seed, _) = get_auction_nft_mint_pda(&authority, 1, &program_id);
+        assert_eq!(index_seed, vec![1]);
+        assert_ne!(key, Pubkey::find_program_address(&[authority.as_ref()], &program_id).0);
+    }
+}
\ No newline at end of file
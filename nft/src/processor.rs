@@ -1,17 +1,32 @@
+use std::slice::Iter;
 use solana_program::{
     msg,
     account_info::{next_account_info, AccountInfo},
     clock::{Clock, UnixTimestamp},
     entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::IsInitialized,
     pubkey::Pubkey,
     sysvar::Sysvar,
 };
+use spl_associated_token_account::get_associated_token_address;
 
 use crate::{
-    invoker::{process_optimal_create_account, process_transfer},
-    nft::{CommonNFTInfo, auction::NFTAuction},
+    invoker::{process_optimal_create_account, process_realloc_account, process_transfer},
+    nft::{
+        CommonNFTInfo,
+        Presale,
+        PurchaseRecord,
+        Royalty,
+        is_valid_metadata,
+        allowlist::AllowlistProof,
+        auction::{NFTAuction, LegacyNFTAuction, NFTAuctionUpdate, BidInfo, BidEscrow, BidHistoryPage, BidExtension, ReservePrice, Consignment, MAX_AUCTION_WINNERS, MAX_MIN_RAISE_BPS},
+        dutch_auction::{DutchAuction, PriceDecay},
+        sealed_auction::{SealedAuction, SealedAuctionKind, SealedBid},
+    },
     key::{KeyInfo, UserKeyRecord},
     Packer,
+    Updater,
     error::VoilaError,
     token::*,
     pda::*,
@@ -26,31 +41,155 @@ pub fn process_instruction(
     let instruction = VoilaInstruction::unpack(input)?;
     match instruction {
         VoilaInstruction::PurchaseKey => process_purchase_key(program_id, accounts),
-        VoilaInstruction::PurchaseCommonNFT => process_purchase_common_nft(program_id, accounts),
+        VoilaInstruction::PurchaseCommonNFT(proof) => process_purchase_common_nft(program_id, accounts, proof),
         #[cfg(feature = "metaplex")]
-        VoilaInstruction::BindCommonNFTOnMetaplex => process_bind_common_nft_on_metaplex(accounts),
-        VoilaInstruction::BidInNFTAuction(raise_price) => process_bid_in_nft_auction(accounts, raise_price),
-        VoilaInstruction::ClaimNFTFromAuction => process_claim_nft_from_auction(program_id, accounts),
+        VoilaInstruction::BindCommonNFTOnMetaplex(nft_id) => process_bind_common_nft_on_metaplex(program_id, accounts, nft_id),
+        VoilaInstruction::BidInNFTAuction(raise_price) => process_bid_in_nft_auction(program_id, accounts, raise_price),
+        VoilaInstruction::ClaimNFTFromAuction(index) => process_claim_nft_from_auction(program_id, accounts, index),
         #[cfg(feature = "metaplex")]
-        VoilaInstruction::BindAuctionNFTOnMetaplex => process_bind_auction_nft_on_metaplex(accounts),
+        VoilaInstruction::BindAuctionNFTOnMetaplex(index) => process_bind_auction_nft_on_metaplex(program_id, accounts, index),
+        VoilaInstruction::ClaimBidRefund => process_claim_bid_refund(program_id, accounts),
+        VoilaInstruction::BuyNowInNFTAuction => process_buy_now_in_nft_auction(program_id, accounts),
+        VoilaInstruction::BuyInDutchAuction => process_buy_in_dutch_auction(program_id, accounts),
+        VoilaInstruction::CommitSealedBid(commitment, deposit) => process_commit_sealed_bid(program_id, accounts, commitment, deposit),
+        VoilaInstruction::RevealSealedBid(price, salt) => process_reveal_sealed_bid(program_id, accounts, price, salt),
+        VoilaInstruction::SettleSealedAuction => process_settle_sealed_auction(program_id, accounts),
+        VoilaInstruction::RefundSealedBid => process_refund_sealed_bid(program_id, accounts),
+        VoilaInstruction::ReclaimConsignedNFT => process_reclaim_consigned_nft(accounts),
+        VoilaInstruction::SettleNFTAuction(index) => process_settle_nft_auction(program_id, accounts, index),
+        VoilaInstruction::BidWithMaxInNFTAuction(max_price) => process_bid_with_max_in_nft_auction(program_id, accounts, max_price),
+        VoilaInstruction::PurchaseCommonNFTBatch(count, proof) => process_purchase_common_nft_batch(program_id, accounts, count, proof),
+        #[cfg(feature = "metaplex")]
+        VoilaInstruction::BindDutchAuctionNFTOnMetaplex => process_bind_dutch_auction_nft_on_metaplex(program_id, accounts),
+        #[cfg(feature = "metaplex")]
+        VoilaInstruction::BindSealedAuctionNFTOnMetaplex => process_bind_sealed_auction_nft_on_metaplex(program_id, accounts),
         VoilaInstruction::CreateKeyInfo(receipt, price) => process_create_key_info(program_id, accounts, receipt, price),
         VoilaInstruction::CreateCommonNFT(
             receipt,
             price,
             max_amount,
+            presale,
+            sale_start,
+            sale_end,
+            max_per_wallet,
+            payment_mint,
+            allowlist_root,
+            royalty,
+            name,
+            uri,
+        ) => process_create_common_nft(
+            program_id,
+            accounts,
+            receipt,
+            price,
+            max_amount,
+            presale,
+            sale_start,
+            sale_end,
+            max_per_wallet,
+            payment_mint,
+            allowlist_root,
+            royalty,
             name,
             uri,
-        ) => process_create_common_nft(program_id, accounts, receipt, price, max_amount, name, uri),
+        ),
         VoilaInstruction::CreateNFTAuction(
             sn,
+            receipt,
+            start_time,
+            end_time,
+            base_price,
+            min_raise_price,
+            min_raise_bps,
+            winners,
+            bid_extension,
+            reserve_price,
+            buy_now_price,
+            payment_mint,
+            consignment,
+            crank_reward,
+            key_gate,
+            royalty,
+            name,
+            uri,
+        ) => process_create_auction_nft(
+            program_id,
+            accounts,
+            sn,
+            receipt,
             start_time,
             end_time,
             base_price,
             min_raise_price,
+            min_raise_bps,
+            winners,
+            bid_extension,
+            reserve_price,
+            buy_now_price,
+            payment_mint,
+            consignment,
+            crank_reward,
+            key_gate,
+            royalty,
             name,
             uri,
-        ) => process_create_auction_nft(program_id, accounts, sn, start_time, end_time, base_price, min_raise_price, name, uri),
+        ),
         VoilaInstruction::WithdrawFromNFTAuction => process_withdraw_from_nft_auction(accounts),
+        VoilaInstruction::UpdateNFTAuction(update) => process_update_nft_auction(accounts, update),
+        VoilaInstruction::CancelNFTAuction => process_cancel_nft_auction(accounts),
+        VoilaInstruction::MigrateNFTAuction => process_migrate_nft_auction(program_id, accounts),
+        #[cfg(feature = "metaplex")]
+        VoilaInstruction::CreateCommonNFTCollection => process_create_common_nft_collection(program_id, accounts),
+        #[cfg(feature = "metaplex")]
+        VoilaInstruction::CreateNFTAuctionCollection => process_create_nft_auction_collection(program_id, accounts),
+        VoilaInstruction::CreateDutchAuction(
+            sn,
+            receipt,
+            start_time,
+            end_time,
+            start_price,
+            floor_price,
+            price_decay,
+            name,
+            uri,
+        ) => process_create_dutch_auction(
+            program_id,
+            accounts,
+            sn,
+            receipt,
+            start_time,
+            end_time,
+            start_price,
+            floor_price,
+            price_decay,
+            name,
+            uri,
+        ),
+        VoilaInstruction::CreateSealedAuction(
+            sn,
+            receipt,
+            kind,
+            start_time,
+            commit_end_time,
+            reveal_end_time,
+            min_price,
+            slash_bps,
+            name,
+            uri,
+        ) => process_create_sealed_auction(
+            program_id,
+            accounts,
+            sn,
+            receipt,
+            kind,
+            start_time,
+            commit_end_time,
+            reveal_end_time,
+            min_price,
+            slash_bps,
+            name,
+            uri,
+        ),
     }
 }
 
@@ -146,13 +285,48 @@ fn process_purchase_key(
         .initialize(&mut user_record_info.try_borrow_mut_data()?)
 }
 
+fn check_user_key_record(
+    program_id: &Pubkey,
+    user_key_record_info: &AccountInfo,
+    key_info: &Pubkey,
+    user_authority: &Pubkey,
+) -> ProgramResult {
+    let (key, _, _, _) = get_user_key_record_pda(key_info, user_authority, program_id);
+    if &key != user_key_record_info.key {
+        msg!("User key record pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+    if user_key_record_info.owner != program_id {
+        msg!("User key record is not owned by the program");
+        return Err(VoilaError::InvalidAccountOwner.into());
+    }
+
+    let user_key_record = UserKeyRecord::unpack(&user_key_record_info.try_borrow_data()?)?;
+    if &user_key_record.key_info != key_info {
+        msg!("User key record is not matched with key info {}", key_info);
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+
+    msg!("User holds key of {}", key_info);
+
+    Ok(())
+}
+
 #[inline(never)]
+#[allow(clippy::too_many_arguments)]
 fn process_create_common_nft(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     receipt: Pubkey,
     price: u64,
     max_amount: u16,
+    presale: Option<Presale>,
+    sale_start: Option<UnixTimestamp>,
+    sale_end: Option<UnixTimestamp>,
+    max_per_wallet: Option<u16>,
+    payment_mint: Option<Pubkey>,
+    allowlist_root: Option<[u8; 32]>,
+    royalty: Option<Royalty>,
     name: String,
     uri: String,
 ) -> ProgramResult {
@@ -172,6 +346,58 @@ fn process_create_common_nft(
 
     msg!("Create common NFT info, price = {}, max amount = {}, name = {}, uri = {}", price, max_amount, name, uri);
 
+    if !is_valid_metadata(&name, &uri, max_amount.saturating_sub(1)) {
+        msg!("Common NFT name or uri is too long for Metaplex metadata");
+        return Err(VoilaError::InvalidParam.into());
+    }
+
+    if let Some(presale) = &presale {
+        if !presale.is_valid() {
+            msg!("Presale end time must be later than start time");
+            return Err(VoilaError::InvalidParam.into());
+        }
+
+        msg!("Presale for holders of key {}, price = {:?}", presale.key_info, presale.price);
+    }
+
+    let nft_info = CommonNFTInfo::new(
+        *admin_authority_info.key,
+        receipt,
+        common_nft_info.key,
+        program_id,
+        price,
+        max_amount,
+        presale,
+        sale_start,
+        sale_end,
+        max_per_wallet,
+        payment_mint,
+        allowlist_root,
+        royalty,
+        name.clone(),
+        uri,
+    );
+    if !nft_info.is_valid_sale_window() {
+        msg!("Sale end time must be later than sale start time");
+        return Err(VoilaError::InvalidParam.into());
+    }
+
+    msg!("Sale window, start = {:?}, end = {:?}, max per wallet = {:?}", sale_start, sale_end, max_per_wallet);
+    if let Some(payment_mint) = &payment_mint {
+        msg!("Payment mint: {}", payment_mint);
+    }
+    if let Some(allowlist_root) = &nft_info.allowlist_root {
+        msg!("Allowlist root: {:?}", allowlist_root);
+    }
+    if let Some(royalty) = &nft_info.royalty {
+        if !royalty.is_valid(&nft_info.pda_authority) {
+            msg!("Royalty creators or seller fee basis points are invalid");
+            return Err(VoilaError::InvalidParam.into());
+        }
+
+        msg!("Royalty: {:?}", royalty);
+    }
+
     process_optimal_create_account(
         rent_info,
         common_nft_info,
@@ -183,25 +409,18 @@ fn process_create_common_nft(
         &[seed_1, seed_2, seed_3, seed_4],
     )?;
 
-    CommonNFTInfo::new(
-        *admin_authority_info.key,
-        receipt,
-        common_nft_info.key,
-        program_id,
-        price,
-        max_amount,
-        name,
-        uri,
-    ).initialize(&mut common_nft_info.try_borrow_mut_data()?)
+    nft_info.initialize(&mut common_nft_info.try_borrow_mut_data()?)
 }
 
 #[inline(never)]
 fn process_purchase_common_nft(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+    proof: Option<AllowlistProof>,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
     let rent_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
@@ -214,7 +433,9 @@ fn process_purchase_common_nft(
     let user_authority_info = next_account_info(account_info_iter)?;
 
     let mut nft_info = CommonNFTInfo::unpack(&common_nft_info.try_borrow_data()?)?;
-    if &nft_info.receipt != receipt_info.key {
+    if let Some(payment_mint) = &nft_info.payment_mint {
+        check_payment_token_account(receipt_info, &nft_info.receipt, payment_mint)?;
+    } else if &nft_info.receipt != receipt_info.key {
         msg!("Receipt account in common NFT info is not matched with provided");
         return Err(VoilaError::UnmatchedAccounts.into());
     }
@@ -223,29 +444,222 @@ fn process_purchase_common_nft(
         return Err(VoilaError::UnmatchedAccounts.into());
     }
 
+    let (price, presale_key_info) = nft_info.purchase_terms(clock.unix_timestamp)?;
+
     msg!("Purchase for common NFT, name = {}, price = {}, current amount = {}, max amount = {}",
-        nft_info.name, nft_info.price, nft_info.current_amount, nft_info.max_amount);
+        nft_info.name, price, nft_info.current_amount, nft_info.max_amount);
 
     if nft_info.current_amount >= nft_info.max_amount {
         return Err(VoilaError::NFTEndOfSale.into());
     }
 
-    let (key, seed_1, ref seed_2, ref seed_3)
-        = get_common_nft_mint_pda(common_nft_info.key, nft_info.current_amount, program_id);
-    if &key != user_nft_mint_info.key {
-        msg!("User common NFT mint pubkey is an invalid pda pubkey");
-        return Err(VoilaError::InvalidPdaPubkey.into());
+    // only key holders can purchase in presale
+    if let Some(key_info) = presale_key_info {
+        let user_key_record_info = next_account_info(account_info_iter)?;
+        check_user_key_record(program_id, user_key_record_info, key_info, user_authority_info.key)?;
+    }
+    // purchases are only recorded when a wallet limit applies
+    if let Some(wallet_limit) = nft_info.wallet_limit(user_authority_info.key, proof.as_ref())? {
+        let purchase_record_info = next_account_info(account_info_iter)?;
+        process_record_purchase(
+            program_id,
+            rent_info,
+            system_program_info,
+            common_nft_info,
+            user_authority_info,
+            purchase_record_info,
+            1,
+            wallet_limit,
+        )?;
     }
 
     // pay for nft
-    process_transfer(
-        user_authority_info,
+    process_common_nft_payment(
+        account_info_iter,
+        token_program_info,
+        system_program_info,
         receipt_info,
+        user_authority_info,
+        &nft_info,
+        price,
+    )?;
+
+    process_mint_common_nft(
+        program_id,
+        rent_info,
         system_program_info,
-        nft_info.price,
-        &[],
+        token_program_info,
+        spl_associated_program_info,
+        common_nft_info,
+        common_nft_authority_info,
+        user_authority_info,
+        user_nft_mint_info,
+        user_nft_account_info,
+        &nft_info,
+        nft_info.current_amount,
+    )?;
+
+    nft_info.current_amount += 1;
+    nft_info.pack(&mut common_nft_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+fn process_purchase_common_nft_batch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    count: u8,
+    proof: Option<AllowlistProof>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let spl_associated_program_info = next_account_info(account_info_iter)?;
+    let common_nft_info = next_account_info(account_info_iter)?;
+    let common_nft_authority_info = next_account_info(account_info_iter)?;
+    let receipt_info = next_account_info(account_info_iter)?;
+    let user_authority_info = next_account_info(account_info_iter)?;
+
+    let mut nft_info = CommonNFTInfo::unpack(&common_nft_info.try_borrow_data()?)?;
+    if let Some(payment_mint) = &nft_info.payment_mint {
+        check_payment_token_account(receipt_info, &nft_info.receipt, payment_mint)?;
+    } else if &nft_info.receipt != receipt_info.key {
+        msg!("Receipt account in common NFT info is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    if &nft_info.pda_authority != common_nft_authority_info.key {
+        msg!("Authority account in common NFT info is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    if count == 0 {
+        msg!("Purchase count must be positive");
+        return Err(VoilaError::InvalidParam.into());
+    }
+
+    let (price, presale_key_info) = nft_info.purchase_terms(clock.unix_timestamp)?;
+    let total_price = price.checked_mul(count as u64).ok_or(VoilaError::MathOverflow)?;
+
+    msg!("Purchase for common NFT in batch, name = {}, count = {}, total price = {}, current amount = {}, max amount = {}",
+        nft_info.name, count, total_price, nft_info.current_amount, nft_info.max_amount);
+
+    let current_amount = nft_info.current_amount
+        .checked_add(count as u16)
+        .ok_or(VoilaError::MathOverflow)?;
+    if current_amount > nft_info.max_amount {
+        return Err(VoilaError::NFTEndOfSale.into());
+    }
+
+    // only key holders can purchase in presale
+    if let Some(key_info) = presale_key_info {
+        let user_key_record_info = next_account_info(account_info_iter)?;
+        check_user_key_record(program_id, user_key_record_info, key_info, user_authority_info.key)?;
+    }
+    // purchases are only recorded when a wallet limit applies
+    if let Some(wallet_limit) = nft_info.wallet_limit(user_authority_info.key, proof.as_ref())? {
+        let purchase_record_info = next_account_info(account_info_iter)?;
+        process_record_purchase(
+            program_id,
+            rent_info,
+            system_program_info,
+            common_nft_info,
+            user_authority_info,
+            purchase_record_info,
+            count as u16,
+            wallet_limit,
+        )?;
+    }
+
+    // pay for all nfts at once
+    process_common_nft_payment(
+        account_info_iter,
+        token_program_info,
+        system_program_info,
+        receipt_info,
+        user_authority_info,
+        &nft_info,
+        total_price,
     )?;
 
+    for id in nft_info.current_amount..current_amount {
+        let user_nft_mint_info = next_account_info(account_info_iter)?;
+        let user_nft_account_info = next_account_info(account_info_iter)?;
+
+        process_mint_common_nft(
+            program_id,
+            rent_info,
+            system_program_info,
+            token_program_info,
+            spl_associated_program_info,
+            common_nft_info,
+            common_nft_authority_info,
+            user_authority_info,
+            user_nft_mint_info,
+            user_nft_account_info,
+            &nft_info,
+            id,
+        )?;
+    }
+
+    nft_info.current_amount = current_amount;
+    nft_info.pack(&mut common_nft_info.try_borrow_mut_data()?)
+}
+
+fn process_common_nft_payment<'a, 'b>(
+    account_info_iter: &mut Iter<'b, AccountInfo<'a>>,
+    token_program_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    receipt_info: &AccountInfo<'a>,
+    user_authority_info: &AccountInfo<'a>,
+    nft_info: &CommonNFTInfo,
+    amount: u64,
+) -> ProgramResult {
+    if let Some(payment_mint) = &nft_info.payment_mint {
+        let user_token_info = next_account_info(account_info_iter)?;
+        check_payment_token_account(user_token_info, user_authority_info.key, payment_mint)?;
+
+        process_token_transfer(
+            token_program_info,
+            user_token_info,
+            receipt_info,
+            user_authority_info,
+            &[],
+            amount,
+        )
+    } else {
+        process_transfer(
+            user_authority_info,
+            receipt_info,
+            system_program_info,
+            amount,
+            &[],
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_mint_common_nft<'a>(
+    program_id: &Pubkey,
+    rent_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    spl_associated_program_info: &AccountInfo<'a>,
+    common_nft_info: &AccountInfo<'a>,
+    common_nft_authority_info: &AccountInfo<'a>,
+    user_authority_info: &AccountInfo<'a>,
+    user_nft_mint_info: &AccountInfo<'a>,
+    user_nft_account_info: &AccountInfo<'a>,
+    nft_info: &CommonNFTInfo,
+    id: u16,
+) -> ProgramResult {
+    let (key, seed_1, ref seed_2, ref seed_3)
+        = get_common_nft_mint_pda(common_nft_info.key, id, program_id);
+    if &key != user_nft_mint_info.key {
+        msg!("User common NFT mint pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+
     process_init_token_mint(
         rent_info,
         user_nft_mint_info,
@@ -277,14 +691,49 @@ fn process_purchase_common_nft(
         common_nft_authority_info,
         &nft_info.authority_signer_seeds(common_nft_info.key),
         1,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_record_purchase<'a>(
+    program_id: &Pubkey,
+    rent_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    common_nft_info: &AccountInfo<'a>,
+    user_authority_info: &AccountInfo<'a>,
+    purchase_record_info: &AccountInfo<'a>,
+    count: u16,
+    max_per_wallet: u16,
+) -> ProgramResult {
+    let (key, seed_1, seed_2, seed_3, ref seed_4)
+        = get_purchase_record_pda(common_nft_info.key, user_authority_info.key, program_id);
+    if purchase_record_info.key != &key {
+        msg!("Purchase record pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+
+    process_optimal_create_account(
+        rent_info,
+        purchase_record_info,
+        user_authority_info,
+        system_program_info,
+        program_id,
+        PurchaseRecord::LEN,
+        &[],
+        &[seed_1, seed_2, seed_3, seed_4],
     )?;
 
-    nft_info.current_amount += 1;
-    nft_info.pack(&mut common_nft_info.try_borrow_mut_data()?)
+    let mut purchase_record = PurchaseRecord::unpack_unchecked(&purchase_record_info.try_borrow_data()?)?;
+    if !purchase_record.is_initialized() {
+        purchase_record = PurchaseRecord::new(*common_nft_info.key, *user_authority_info.key);
+    }
+    purchase_record.purchase(count, max_per_wallet)?;
+
+    purchase_record.pack(&mut purchase_record_info.try_borrow_mut_data()?)
 }
 
 #[cfg(feature = "metaplex")]
-fn process_bind_common_nft_on_metaplex(accounts: &[AccountInfo]) -> ProgramResult {
+fn process_bind_common_nft_on_metaplex(program_id: &Pubkey, accounts: &[AccountInfo], nft_id: u16) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
     let rent_info = next_account_info(account_info_iter)?;
@@ -298,21 +747,37 @@ fn process_bind_common_nft_on_metaplex(accounts: &[AccountInfo]) -> ProgramResul
     let master_edition_account_info = next_account_info(account_info_iter)?;
     let user_authority_info = next_account_info(account_info_iter)?;
 
-    use crate::nft::{metaplex::{process_invoke_metaplex_create_metadata_accounts, process_invoke_metaplex_create_master_edition_accounts}, Meta};
+    use crate::nft::{
+        metaplex::{
+            process_invoke_metaplex_create_metadata_accounts,
+            process_invoke_metaplex_create_master_edition_accounts,
+            process_invoke_metaplex_sign_metadata,
+        },
+        Meta,
+    };
+
+    check_metaplex_program(metaplex_program_info)?;
 
     let nft_info = CommonNFTInfo::unpack(&common_nft_info.try_borrow_data()?)?;
     if &nft_info.pda_authority != common_nft_authority_info.key {
         msg!("Authority account in common NFT info is not matched with provided");
         return Err(VoilaError::UnmatchedAccounts.into());
     }
+    let (key, _, _, _) = get_common_nft_mint_pda(common_nft_info.key, nft_id, program_id);
+    if &key != user_nft_mint_info.key {
+        msg!("User common NFT mint pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
     let signer_seeds = &nft_info.authority_signer_seeds(common_nft_info.key);
-    let data = nft_info.metadata(user_nft_mint_info.key);
+    let data = nft_info.metadata(nft_id);
 
+    // the PDA authority stays the update authority, so holders can not rewrite the royalties
     process_invoke_metaplex_create_metadata_accounts(
         metaplex_program_info,
         metadata_account_info,
         user_nft_mint_info,
         common_nft_authority_info,
+        common_nft_authority_info,
         user_authority_info,
         system_program_info,
         rent_info,
@@ -320,58 +785,391 @@ fn process_bind_common_nft_on_metaplex(accounts: &[AccountInfo]) -> ProgramResul
         signer_seeds,
     )?;
 
+    // verify the PDA authority as creator
+    if nft_info.royalty.is_some() {
+        process_invoke_metaplex_sign_metadata(
+            metaplex_program_info,
+            metadata_account_info,
+            common_nft_authority_info,
+            signer_seeds,
+        )?;
+    }
+
     process_invoke_metaplex_create_master_edition_accounts(
         metaplex_program_info,
         metadata_account_info,
         master_edition_account_info,
         user_nft_mint_info,
         common_nft_authority_info,
+        common_nft_authority_info,
         user_authority_info,
         token_program_info,
         system_program_info,
         rent_info,
+        None,
         signer_seeds,
-    )
+    )?;
+
+    if let Some(collection) = &nft_info.collection {
+        process_verify_collection(
+            account_info_iter,
+            metaplex_program_info,
+            metadata_account_info,
+            common_nft_authority_info,
+            user_authority_info,
+            collection,
+            signer_seeds,
+        )?;
+    }
+
+    Ok(())
 }
 
-#[inline(never)]
-#[allow(clippy::too_many_arguments)]
-fn process_create_auction_nft(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    sn: u16,
-    start_time: UnixTimestamp,
-    end_time: UnixTimestamp,
-    base_price: u64,
-    min_raise_price: u64,
-    name: String,
-    uri: String,
-) -> ProgramResult {
+#[cfg(feature = "metaplex")]
+fn process_create_common_nft_collection(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
     let rent_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
-    let nft_auction_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let spl_associated_program_info = next_account_info(account_info_iter)?;
+    let metaplex_program_info = next_account_info(account_info_iter)?;
+    let common_nft_info = next_account_info(account_info_iter)?;
+    let common_nft_authority_info = next_account_info(account_info_iter)?;
+    let collection_mint_info = next_account_info(account_info_iter)?;
+    let collection_account_info = next_account_info(account_info_iter)?;
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let master_edition_account_info = next_account_info(account_info_iter)?;
     let admin_authority_info = next_account_info(account_info_iter)?;
 
-    let (key, seed_1, seed_2, ref seed_3, ref seed_4)
-        = get_nft_auction_pda(admin_authority_info.key, sn, program_id);
-    if nft_auction_info.key != &key {
-        msg!("NFT auction info pubkey is an invalid pda pubkey");
-        return Err(VoilaError::InvalidPdaPubkey.into()); 
-    }
+    use crate::nft::Meta;
 
-    msg!(
-        "Create NFT auction info, sn = {}, start time: {}, end time: {}, base price: {}, min raise price: {}",
-        sn,
-        start_time,
-        end_time,
-        base_price,
-        min_raise_price,
-    );
+    check_metaplex_program(metaplex_program_info)?;
 
-    process_optimal_create_account(
-        rent_info,
+    let mut nft_info = CommonNFTInfo::unpack(&common_nft_info.try_borrow_data()?)?;
+    if !admin_authority_info.is_signer {
+        msg!("Admin account is not a signer");
+        return Err(VoilaError::InvalidAuthority.into());
+    }
+    if admin_authority_info.key != &nft_info.admin {
+        msg!("Admin account is not matched with common NFT admin");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    if &nft_info.pda_authority != common_nft_authority_info.key {
+        msg!("Authority account in common NFT info is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    if nft_info.collection.is_some() {
+        msg!("Common NFT collection is already created");
+        return Err(VoilaError::AlreadyInitialized.into());
+    }
+
+    process_create_collection_nft(
+        program_id,
+        rent_info,
+        system_program_info,
+        token_program_info,
+        spl_associated_program_info,
+        metaplex_program_info,
+        common_nft_authority_info,
+        collection_mint_info,
+        collection_account_info,
+        metadata_account_info,
+        master_edition_account_info,
+        admin_authority_info,
+        nft_info.collection_metadata(),
+        &nft_info.authority_signer_seeds(common_nft_info.key),
+    )?;
+
+    msg!("Common NFT collection: {}", collection_mint_info.key);
+    nft_info.collection = Some(*collection_mint_info.key);
+
+    nft_info.pack(&mut common_nft_info.try_borrow_mut_data()?)
+}
+
+#[cfg(feature = "metaplex")]
+#[allow(clippy::too_many_arguments)]
+fn process_create_collection_nft<'a>(
+    program_id: &Pubkey,
+    rent_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    spl_associated_program_info: &AccountInfo<'a>,
+    metaplex_program_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    collection_mint_info: &AccountInfo<'a>,
+    collection_account_info: &AccountInfo<'a>,
+    metadata_account_info: &AccountInfo<'a>,
+    master_edition_account_info: &AccountInfo<'a>,
+    admin_authority_info: &AccountInfo<'a>,
+    data: mpl_token_metadata::state::DataV2,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    use crate::nft::metaplex::{
+        process_invoke_metaplex_create_metadata_accounts,
+        process_invoke_metaplex_create_master_edition_accounts,
+        process_invoke_metaplex_sign_metadata,
+    };
+
+    let (key, seed_1, seed_2, ref seed_3) = get_collection_mint_pda(authority_info.key, program_id);
+    if &key != collection_mint_info.key {
+        msg!("Collection mint pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+
+    process_init_token_mint(
+        rent_info,
+        collection_mint_info,
+        admin_authority_info,
+        token_program_info,
+        system_program_info,
+        authority_info.key,
+        0,
+        &[],
+        &[seed_1, seed_2, seed_3],
+    )?;
+
+    process_create_associated_token_account(
+        rent_info,
+        collection_mint_info,
+        collection_account_info,
+        admin_authority_info,
+        authority_info,
+        token_program_info,
+        system_program_info,
+        spl_associated_program_info,
+        &[],
+    )?;
+
+    process_token_mint_to(
+        token_program_info,
+        collection_mint_info,
+        collection_account_info,
+        authority_info,
+        signer_seeds,
+        1,
+    )?;
+
+    let has_creators = data.creators.is_some();
+    process_invoke_metaplex_create_metadata_accounts(
+        metaplex_program_info,
+        metadata_account_info,
+        collection_mint_info,
+        authority_info,
+        authority_info,
+        admin_authority_info,
+        system_program_info,
+        rent_info,
+        data,
+        signer_seeds,
+    )?;
+
+    // verify the PDA authority as creator
+    if has_creators {
+        process_invoke_metaplex_sign_metadata(
+            metaplex_program_info,
+            metadata_account_info,
+            authority_info,
+            signer_seeds,
+        )?;
+    }
+
+    process_invoke_metaplex_create_master_edition_accounts(
+        metaplex_program_info,
+        metadata_account_info,
+        master_edition_account_info,
+        collection_mint_info,
+        authority_info,
+        authority_info,
+        admin_authority_info,
+        token_program_info,
+        system_program_info,
+        rent_info,
+        Some(0),
+        signer_seeds,
+    )
+}
+
+/// Rejects any program other than Metaplex before it is invoked with signer seeds of the PDA authority
+#[cfg(feature = "metaplex")]
+fn check_metaplex_program(metaplex_program_info: &AccountInfo) -> ProgramResult {
+    if metaplex_program_info.key != &mpl_token_metadata::ID {
+        msg!("Metaplex program is invalid");
+        return Err(VoilaError::InvalidProgramId.into());
+    }
+
+    Ok(())
+}
+
+/// Verifies the minted NFT as a member of the collection, collection accounts follow the bind accounts
+#[cfg(feature = "metaplex")]
+fn process_verify_collection<'a, 'b>(
+    account_info_iter: &mut Iter<'b, AccountInfo<'a>>,
+    metaplex_program_info: &AccountInfo<'a>,
+    metadata_account_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    payer_authority_info: &AccountInfo<'a>,
+    collection: &Pubkey,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let collection_mint_info = next_account_info(account_info_iter)?;
+    let collection_metadata_account_info = next_account_info(account_info_iter)?;
+    let collection_master_edition_account_info = next_account_info(account_info_iter)?;
+
+    use crate::nft::metaplex::process_invoke_metaplex_verify_collection;
+
+    if collection_mint_info.key != collection {
+        msg!("Collection mint is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+
+    process_invoke_metaplex_verify_collection(
+        metaplex_program_info,
+        metadata_account_info,
+        authority_info,
+        payer_authority_info,
+        collection_mint_info,
+        collection_metadata_account_info,
+        collection_master_edition_account_info,
+        signer_seeds,
+    )
+}
+
+#[inline(never)]
+#[allow(clippy::too_many_arguments)]
+fn process_create_auction_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    sn: u16,
+    receipt: Pubkey,
+    start_time: UnixTimestamp,
+    end_time: UnixTimestamp,
+    base_price: u64,
+    min_raise_price: u64,
+    min_raise_bps: u16,
+    winners: u8,
+    bid_extension: Option<BidExtension>,
+    reserve_price: Option<ReservePrice>,
+    buy_now_price: Option<u64>,
+    payment_mint: Option<Pubkey>,
+    consignment: Option<Consignment>,
+    crank_reward: Option<u64>,
+    key_gate: Option<Pubkey>,
+    royalty: Option<Royalty>,
+    name: String,
+    uri: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let admin_authority_info = next_account_info(account_info_iter)?;
+
+    let (key, seed_1, seed_2, ref seed_3, ref seed_4)
+        = get_nft_auction_pda(admin_authority_info.key, sn, program_id);
+    if nft_auction_info.key != &key {
+        msg!("NFT auction info pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into()); 
+    }
+
+    msg!(
+        "Create NFT auction info, sn = {}, receipt: {}, start time: {}, end time: {}, base price: {}, min raise price: {}",
+        sn,
+        receipt,
+        start_time,
+        end_time,
+        base_price,
+        min_raise_price,
+    );
+
+    if min_raise_bps > MAX_MIN_RAISE_BPS {
+        msg!("NFT auction min raise bps should not exceed {}", MAX_MIN_RAISE_BPS);
+        return Err(VoilaError::InvalidParam.into());
+    }
+    msg!("Min raise bps: {}", min_raise_bps);
+
+    if winners == 0 || winners > MAX_AUCTION_WINNERS {
+        msg!("NFT auction winners should be between 1 and {}", MAX_AUCTION_WINNERS);
+        return Err(VoilaError::InvalidParam.into());
+    }
+    msg!("Winners: {}", winners);
+
+    if !is_valid_metadata(&name, &uri, winners as u16 - 1) {
+        msg!("NFT auction name or uri is too long for Metaplex metadata");
+        return Err(VoilaError::InvalidParam.into());
+    }
+
+    if let Some(bid_extension) = &bid_extension {
+        if !bid_extension.is_valid(end_time) {
+            msg!("Bid extension is invalid");
+            return Err(VoilaError::InvalidParam.into());
+        }
+
+        msg!(
+            "Bid extension window: {}, duration: {}, max end time: {:?}",
+            bid_extension.window,
+            bid_extension.duration,
+            bid_extension.max_end_time,
+        );
+    }
+
+    if let Some(reserve_price) = &reserve_price {
+        if reserve_price.price < base_price {
+            msg!("Reserve price is lower than base price");
+            return Err(VoilaError::InvalidParam.into());
+        }
+
+        if reserve_price.hidden {
+            msg!("Reserve price is hidden");
+        } else {
+            msg!("Reserve price: {}", reserve_price.price);
+        }
+    }
+
+    if let Some(buy_now_price) = buy_now_price {
+        if winners > 1 {
+            msg!("Buy now is only available in single winner auction");
+            return Err(VoilaError::InvalidParam.into());
+        }
+        let min_buy_now_price = reserve_price.map_or(base_price, |reserve_price| reserve_price.price);
+        if buy_now_price <= base_price || buy_now_price < min_buy_now_price {
+            msg!("Buy now price is lower than base price or reserve price");
+            return Err(VoilaError::InvalidParam.into());
+        }
+
+        msg!("Buy now price: {}", buy_now_price);
+    }
+
+    if let Some(consignment) = &consignment {
+        if winners > 1 {
+            msg!("Consignment is only available in single winner auction");
+            return Err(VoilaError::InvalidParam.into());
+        }
+        if !consignment.is_valid() {
+            msg!("Consignment house fee is invalid");
+            return Err(VoilaError::InvalidParam.into());
+        }
+
+        msg!(
+            "Consigned NFT mint: {}, seller: {}, house fee bps: {}",
+            consignment.mint,
+            consignment.seller,
+            consignment.house_fee_bps,
+        );
+    }
+
+    if let Some(crank_reward) = crank_reward {
+        if crank_reward > base_price {
+            msg!("Crank reward is higher than base price");
+            return Err(VoilaError::InvalidParam.into());
+        }
+
+        msg!("Crank reward: {}", crank_reward);
+    }
+
+    process_optimal_create_account(
+        rent_info,
         nft_auction_info,
         admin_authority_info,
         system_program_info,
@@ -381,204 +1179,1761 @@ fn process_create_auction_nft(
         &[seed_1, seed_2, seed_3, seed_4],
     )?;
 
-    NFTAuction::new(
-        *admin_authority_info.key,
-        nft_auction_info.key,
-        program_id,
-        start_time,
-        end_time,
-        base_price,
-        min_raise_price,
-        name,
-        uri,
-    ).initialize(&mut nft_auction_info.try_borrow_mut_data()?)
+    let nft_auction = NFTAuction::new(
+        *admin_authority_info.key,
+        receipt,
+        nft_auction_info.key,
+        program_id,
+        start_time,
+        end_time,
+        base_price,
+        min_raise_price,
+        min_raise_bps,
+        winners,
+        bid_extension,
+        reserve_price,
+        buy_now_price,
+        payment_mint,
+        consignment,
+        crank_reward,
+        key_gate,
+        royalty,
+        name,
+        uri,
+    );
+    if let Some(royalty) = &nft_auction.royalty {
+        if !royalty.is_valid(&nft_auction.pda_authority) {
+            msg!("Royalty creators or seller fee basis points are invalid");
+            return Err(VoilaError::InvalidParam.into());
+        }
+
+        msg!("Royalty: {:?}", royalty);
+    }
+
+    // create escrow token account for SPL token bids
+    if let Some(payment_mint) = &payment_mint {
+        let token_program_info = next_account_info(account_info_iter)?;
+        let spl_associated_program_info = next_account_info(account_info_iter)?;
+        let nft_auction_authority_info = next_account_info(account_info_iter)?;
+        let payment_mint_info = next_account_info(account_info_iter)?;
+        let escrow_info = next_account_info(account_info_iter)?;
+
+        if nft_auction_authority_info.key != &nft_auction.pda_authority {
+            msg!("NFT auction authority is not matched with provided");
+            return Err(VoilaError::UnmatchedAccounts.into());
+        }
+        if payment_mint_info.key != payment_mint {
+            msg!("Payment mint is not matched with provided");
+            return Err(VoilaError::UnmatchedAccounts.into());
+        }
+
+        msg!("Payment mint: {}", payment_mint);
+
+        process_create_associated_token_account(
+            rent_info,
+            payment_mint_info,
+            escrow_info,
+            admin_authority_info,
+            nft_auction_authority_info,
+            token_program_info,
+            system_program_info,
+            spl_associated_program_info,
+            &[],
+        )?;
+    }
+
+    // move consigned NFT into escrow token account of auction authority
+    if let Some(consignment) = &consignment {
+        let token_program_info = next_account_info(account_info_iter)?;
+        let spl_associated_program_info = next_account_info(account_info_iter)?;
+        let nft_auction_authority_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let seller_info = next_account_info(account_info_iter)?;
+        let seller_nft_account_info = next_account_info(account_info_iter)?;
+        let nft_escrow_info = next_account_info(account_info_iter)?;
+
+        if nft_auction_authority_info.key != &nft_auction.pda_authority {
+            msg!("NFT auction authority is not matched with provided");
+            return Err(VoilaError::UnmatchedAccounts.into());
+        }
+        if nft_mint_info.key != &consignment.mint {
+            msg!("Consigned NFT mint is not matched with provided");
+            return Err(VoilaError::UnmatchedAccounts.into());
+        }
+        if seller_info.key != &consignment.seller || !seller_info.is_signer {
+            msg!("Seller account is not matched with provided or not a signer");
+            return Err(VoilaError::InvalidAuthority.into());
+        }
+        if nft_escrow_info.key != &get_associated_token_address(nft_auction_authority_info.key, nft_mint_info.key) {
+            msg!("NFT escrow account is not matched with provided");
+            return Err(VoilaError::UnmatchedAccounts.into());
+        }
+
+        process_create_associated_token_account(
+            rent_info,
+            nft_mint_info,
+            nft_escrow_info,
+            admin_authority_info,
+            nft_auction_authority_info,
+            token_program_info,
+            system_program_info,
+            spl_associated_program_info,
+            &[],
+        )?;
+
+        process_token_transfer(
+            token_program_info,
+            seller_nft_account_info,
+            nft_escrow_info,
+            seller_info,
+            &[],
+            1,
+        )?;
+    }
+
+    nft_auction.initialize(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+fn process_update_nft_auction(accounts: &[AccountInfo], update: NFTAuctionUpdate) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    check_nft_auction_admin(admin_info, &nft_auction)?;
+
+    msg!("Update NFT auction, update: {:?}", update);
+
+    nft_auction.update(update)?;
+    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+fn process_cancel_nft_auction(accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let spl_associated_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    if nft_auction_authority_info.key != &nft_auction.pda_authority {
+        msg!("NFT auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    check_nft_auction_admin(admin_info, &nft_auction)?;
+    nft_auction.cancel(clock.unix_timestamp)?;
+
+    msg!("Cancel NFT auction, name = {}, bids in escrow are all refundable", nft_auction.name);
+
+    // return consigned NFT to the seller
+    if let Some(consignment) = &nft_auction.consignment {
+        let seller_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let seller_nft_account_info = next_account_info(account_info_iter)?;
+        let nft_escrow_info = next_account_info(account_info_iter)?;
+
+        if seller_info.key != &consignment.seller {
+            msg!("Seller account in NFT auction is not matched with provided");
+            return Err(VoilaError::UnmatchedAccounts.into());
+        }
+
+        process_transfer_consigned_nft(
+            rent_info,
+            system_program_info,
+            token_program_info,
+            spl_associated_program_info,
+            nft_auction_authority_info,
+            admin_info,
+            seller_info,
+            nft_mint_info,
+            seller_nft_account_info,
+            nft_escrow_info,
+            consignment,
+            &nft_auction.authority_signer_seeds(nft_auction_info.key),
+        )?;
+    }
+
+    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+fn process_migrate_nft_auction(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if nft_auction_info.owner != program_id {
+        msg!("NFT auction is not owned by the program");
+        return Err(VoilaError::InvalidAccountOwner.into());
+    }
+    if nft_auction_info.data_len() != LegacyNFTAuction::LEN {
+        msg!("NFT auction is not in the legacy layout");
+        return Err(VoilaError::InvalidParam.into());
+    }
+
+    let mut nft_auction: NFTAuction = LegacyNFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?.into();
+    if nft_auction_authority_info.key != &nft_auction.pda_authority {
+        msg!("NFT auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    check_nft_auction_admin(admin_info, &nft_auction)?;
+
+    msg!("Migrate NFT auction, name = {}", nft_auction.name);
+
+    if let Some(bid_info) = nft_auction.winning_bids.first().copied() {
+        // legacy withdrawal drains the authority, leaving nothing of the leader to escrow
+        if nft_auction_authority_info.lamports() >= bid_info.price {
+            let bid_escrow_info = next_account_info(account_info_iter)?;
+            let (key, seed_1, seed_2, seed_3, ref seed_4)
+                = get_bid_escrow_pda(nft_auction_info.key, &bid_info.bidder, program_id);
+            if bid_escrow_info.key != &key {
+                msg!("Bid escrow pubkey is an invalid pda pubkey");
+                return Err(VoilaError::InvalidPdaPubkey.into());
+            }
+
+            process_optimal_create_account(
+                rent_info,
+                bid_escrow_info,
+                admin_info,
+                system_program_info,
+                program_id,
+                BidEscrow::LEN,
+                &[],
+                &[seed_1, seed_2, seed_3, seed_4],
+            )?;
+
+            let mut bid_escrow = BidEscrow::new(*nft_auction_info.key, bid_info.bidder, None);
+            bid_escrow.deposit(bid_info.price)?;
+            bid_escrow.initialize(&mut bid_escrow_info.try_borrow_mut_data()?)?;
+        } else if clock.unix_timestamp >= nft_auction.end_time {
+            nft_auction.withdrawn = true;
+        } else {
+            msg!("Bid of the leader is withdrawn before the end of bidding");
+            return Err(VoilaError::InvalidParam.into());
+        }
+    }
+
+    process_realloc_account(
+        rent_info,
+        nft_auction_info,
+        admin_info,
+        system_program_info,
+        NFTAuction::LEN,
+    )?;
+
+    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+fn check_nft_auction_admin(admin_info: &AccountInfo, nft_auction: &NFTAuction) -> ProgramResult {
+    if !admin_info.is_signer {
+        msg!("Admin account is not a signer");
+        return Err(VoilaError::InvalidAuthority.into());
+    }
+    if admin_info.key != &nft_auction.admin {
+        msg!("Admin account is not matched with NFT auction admin");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "metaplex")]
+fn process_create_nft_auction_collection(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let spl_associated_program_info = next_account_info(account_info_iter)?;
+    let metaplex_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let collection_mint_info = next_account_info(account_info_iter)?;
+    let collection_account_info = next_account_info(account_info_iter)?;
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let master_edition_account_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    use crate::nft::Meta;
+
+    check_metaplex_program(metaplex_program_info)?;
+
+    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    check_nft_auction_admin(admin_info, &nft_auction)?;
+    if nft_auction_authority_info.key != &nft_auction.pda_authority {
+        msg!("NFT auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    if nft_auction.collection.is_some() {
+        msg!("NFT auction collection is already created");
+        return Err(VoilaError::AlreadyInitialized.into());
+    }
+
+    process_create_collection_nft(
+        program_id,
+        rent_info,
+        system_program_info,
+        token_program_info,
+        spl_associated_program_info,
+        metaplex_program_info,
+        nft_auction_authority_info,
+        collection_mint_info,
+        collection_account_info,
+        metadata_account_info,
+        master_edition_account_info,
+        admin_info,
+        nft_auction.collection_metadata(),
+        &nft_auction.authority_signer_seeds(nft_auction_info.key),
+    )?;
+
+    msg!("NFT auction collection: {}", collection_mint_info.key);
+    nft_auction.collection = Some(*collection_mint_info.key);
+
+    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+fn check_nft_auction_key_gate(
+    program_id: &Pubkey,
+    account_info_iter: &mut Iter<AccountInfo>,
+    nft_auction: &NFTAuction,
+    bidder_info: &AccountInfo,
+) -> ProgramResult {
+    if let Some(key_gate) = &nft_auction.key_gate {
+        let user_key_record_info = next_account_info(account_info_iter)?;
+        check_user_key_record(program_id, user_key_record_info, key_gate, bidder_info.key)?;
+    }
+
+    Ok(())
+}
+
+fn next_escrow_account_infos<'a, 'b>(
+    account_info_iter: &mut Iter<'b, AccountInfo<'a>>,
+    nft_auction: &NFTAuction,
+) -> Result<Option<(&'b AccountInfo<'a>, &'b AccountInfo<'a>)>, ProgramError> {
+    if let Some(payment_mint) = &nft_auction.payment_mint {
+        let token_program_info = next_account_info(account_info_iter)?;
+        let escrow_info = next_account_info(account_info_iter)?;
+
+        if token_program_info.key != &spl_token::ID {
+            msg!("Token program is invalid");
+            return Err(VoilaError::InvalidProgramId.into());
+        }
+        if escrow_info.key != &get_associated_token_address(&nft_auction.pda_authority, payment_mint) {
+            msg!("NFT auction escrow account is not matched with provided");
+            return Err(VoilaError::UnmatchedAccounts.into());
+        }
+
+        Ok(Some((token_program_info, escrow_info)))
+    } else {
+        Ok(None)
+    }
+}
+
+fn check_payment_token_account(
+    token_account_info: &AccountInfo,
+    owner: &Pubkey,
+    payment_mint: &Pubkey,
+) -> ProgramResult {
+    if token_account_info.key != &get_associated_token_address(owner, payment_mint) {
+        msg!("Payment token account is not an associated token account of owner");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+
+    Ok(())
+}
+
+#[inline(never)]
+fn process_withdraw_from_nft_auction(accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let receipt_info = next_account_info(account_info_iter)?;
+
+    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    if nft_auction_authority_info.key != &nft_auction.pda_authority {
+        msg!("NFT auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+
+    check_nft_auction_admin(admin_info, &nft_auction)?;
+    let proceeds = nft_auction.withdraw(clock.unix_timestamp)?;
+    let house_proceeds = if let Some(consignment) = &nft_auction.consignment {
+        consignment.house_fee(proceeds)?
+    } else {
+        proceeds
+    };
+    let seller_proceeds = proceeds - house_proceeds;
+
+    msg!("Withdraw from NFT auction, proceeds: {}, house: {}, seller: {}", proceeds, house_proceeds, seller_proceeds);
+
+    let signer_seeds = &nft_auction.authority_signer_seeds(nft_auction_info.key);
+    if let Some((token_program_info, escrow_info)) = next_escrow_account_infos(account_info_iter, &nft_auction)? {
+        let payment_mint = nft_auction.payment_mint.as_ref().unwrap();
+        check_payment_token_account(receipt_info, &nft_auction.receipt, payment_mint)?;
+
+        process_token_transfer(
+            token_program_info,
+            escrow_info,
+            receipt_info,
+            nft_auction_authority_info,
+            signer_seeds,
+            house_proceeds,
+        )?;
+
+        if let Some(consignment) = &nft_auction.consignment {
+            let seller_token_info = next_account_info(account_info_iter)?;
+            check_payment_token_account(seller_token_info, &consignment.seller, payment_mint)?;
+
+            process_token_transfer(
+                token_program_info,
+                escrow_info,
+                seller_token_info,
+                nft_auction_authority_info,
+                signer_seeds,
+                seller_proceeds,
+            )?;
+        }
+    } else {
+        if receipt_info.key != &nft_auction.receipt {
+            msg!("Receipt account in NFT auction is not matched with provided");
+            return Err(VoilaError::UnmatchedAccounts.into());
+        }
+
+        process_transfer(
+            nft_auction_authority_info,
+            receipt_info,
+            system_program_info,
+            house_proceeds,
+            signer_seeds,
+        )?;
+
+        if let Some(consignment) = &nft_auction.consignment {
+            let seller_info = next_account_info(account_info_iter)?;
+            if seller_info.key != &consignment.seller {
+                msg!("Seller account in NFT auction is not matched with provided");
+                return Err(VoilaError::UnmatchedAccounts.into());
+            }
+
+            process_transfer(
+                nft_auction_authority_info,
+                seller_info,
+                system_program_info,
+                seller_proceeds,
+                signer_seeds,
+            )?;
+        }
+    }
+
+    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+fn process_bid_in_nft_auction(program_id: &Pubkey, accounts: &[AccountInfo], raise_price: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let new_bidder_info = next_account_info(account_info_iter)?;
+    let bid_escrow_info = next_account_info(account_info_iter)?;
+    let bid_history_page_info = next_account_info(account_info_iter)?;
+    let next_bid_history_page_info = next_account_info(account_info_iter)?;
+
+    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    if nft_auction_authority_info.key != &nft_auction.pda_authority {
+        msg!("NFT auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    check_nft_auction_key_gate(program_id, account_info_iter, &nft_auction, new_bidder_info)?;
+    let highest_price = nft_auction.highest_bid().map(|bid_info| bid_info.price);
+    let (bid_info, displaced_bid_info) = nft_auction.bid(raise_price, clock.unix_timestamp, *new_bidder_info.key)?;
+
+    msg!("Bid in NFT auction, bid price: {}, highest price: {}", bid_info.price, nft_auction.highest_bid().unwrap().price);
+    if let Some(displaced_bid_info) = displaced_bid_info {
+        msg!("Displaced bidder {} can claim refund: {}", displaced_bid_info.bidder, displaced_bid_info.price);
+    }
+
+    process_nft_auction_deposit(
+        program_id,
+        account_info_iter,
+        rent_info,
+        system_program_info,
+        nft_auction_info,
+        nft_auction_authority_info,
+        new_bidder_info,
+        bid_escrow_info,
+        &nft_auction,
+        nft_auction.locked_amount(clock.unix_timestamp, new_bidder_info.key).max(bid_info.price),
+    )?;
+    let raised_leader_bid_info = nft_auction.raised_leader_bid(new_bidder_info.key, highest_price, clock.unix_timestamp);
+    for bid_info in std::iter::once(bid_info).chain(raised_leader_bid_info) {
+        process_record_bid_history(
+            program_id,
+            rent_info,
+            system_program_info,
+            nft_auction_info,
+            new_bidder_info,
+            &[bid_history_page_info, next_bid_history_page_info],
+            &mut nft_auction,
+            bid_info,
+        )?;
+    }
+
+    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+fn process_bid_with_max_in_nft_auction(program_id: &Pubkey, accounts: &[AccountInfo], max_price: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let new_bidder_info = next_account_info(account_info_iter)?;
+    let bid_escrow_info = next_account_info(account_info_iter)?;
+    let bid_history_page_info = next_account_info(account_info_iter)?;
+    let next_bid_history_page_info = next_account_info(account_info_iter)?;
+
+    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    if nft_auction_authority_info.key != &nft_auction.pda_authority {
+        msg!("NFT auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    check_nft_auction_key_gate(program_id, account_info_iter, &nft_auction, new_bidder_info)?;
+    let highest_price = nft_auction.highest_bid().map(|bid_info| bid_info.price);
+    let (bid_info, displaced_bid_info) = nft_auction.bid_with_max(max_price, clock.unix_timestamp, *new_bidder_info.key)?;
+
+    msg!("Bid with max in NFT auction, bid price: {}, highest price: {}", bid_info.price, nft_auction.highest_bid().unwrap().price);
+    if let Some(displaced_bid_info) = displaced_bid_info {
+        msg!("Displaced bidder {} can claim refund: {}", displaced_bid_info.bidder, displaced_bid_info.price);
+    }
+
+    process_nft_auction_deposit(
+        program_id,
+        account_info_iter,
+        rent_info,
+        system_program_info,
+        nft_auction_info,
+        nft_auction_authority_info,
+        new_bidder_info,
+        bid_escrow_info,
+        &nft_auction,
+        nft_auction.locked_amount(clock.unix_timestamp, new_bidder_info.key).max(bid_info.price).max(max_price),
+    )?;
+    // the leader raising its own maximum leaves the visible bid unchanged
+    if nft_auction.highest_bid().map(|bid_info| bid_info.price) != highest_price {
+        let raised_leader_bid_info = nft_auction.raised_leader_bid(new_bidder_info.key, highest_price, clock.unix_timestamp);
+        for bid_info in std::iter::once(bid_info).chain(raised_leader_bid_info) {
+            process_record_bid_history(
+                program_id,
+                rent_info,
+                system_program_info,
+                nft_auction_info,
+                new_bidder_info,
+                &[bid_history_page_info, next_bid_history_page_info],
+                &mut nft_auction,
+                bid_info,
+            )?;
+        }
+    }
+
+    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+fn process_buy_now_in_nft_auction(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let buyer_info = next_account_info(account_info_iter)?;
+    let bid_escrow_info = next_account_info(account_info_iter)?;
+    let bid_history_page_info = next_account_info(account_info_iter)?;
+    let next_bid_history_page_info = next_account_info(account_info_iter)?;
+
+    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    if nft_auction_authority_info.key != &nft_auction.pda_authority {
+        msg!("NFT auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    check_nft_auction_key_gate(program_id, account_info_iter, &nft_auction, buyer_info)?;
+    let (bid_info, displaced_bid_info) = nft_auction.buy_now(clock.unix_timestamp, *buyer_info.key)?;
+
+    msg!("Buy now in NFT auction, price: {}", bid_info.price);
+    if let Some(displaced_bid_info) = displaced_bid_info {
+        msg!("Displaced bidder {} can claim refund: {}", displaced_bid_info.bidder, displaced_bid_info.price);
+    }
+
+    process_nft_auction_deposit(
+        program_id,
+        account_info_iter,
+        rent_info,
+        system_program_info,
+        nft_auction_info,
+        nft_auction_authority_info,
+        buyer_info,
+        bid_escrow_info,
+        &nft_auction,
+        nft_auction.locked_amount(clock.unix_timestamp, buyer_info.key).max(bid_info.price),
+    )?;
+    process_record_bid_history(
+        program_id,
+        rent_info,
+        system_program_info,
+        nft_auction_info,
+        buyer_info,
+        &[bid_history_page_info, next_bid_history_page_info],
+        &mut nft_auction,
+        bid_info,
+    )?;
+
+    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_nft_auction_deposit<'a, 'b>(
+    program_id: &Pubkey,
+    account_info_iter: &mut Iter<'b, AccountInfo<'a>>,
+    rent_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    nft_auction_info: &AccountInfo<'a>,
+    nft_auction_authority_info: &AccountInfo<'a>,
+    bidder_info: &AccountInfo<'a>,
+    bid_escrow_info: &AccountInfo<'a>,
+    nft_auction: &NFTAuction,
+    required_amount: u64,
+) -> ProgramResult {
+    let (key, seed_1, seed_2, seed_3, ref seed_4)
+        = get_bid_escrow_pda(nft_auction_info.key, bidder_info.key, program_id);
+    if bid_escrow_info.key != &key {
+        msg!("Bid escrow pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+
+    process_optimal_create_account(
+        rent_info,
+        bid_escrow_info,
+        bidder_info,
+        system_program_info,
+        program_id,
+        BidEscrow::LEN,
+        &[],
+        &[seed_1, seed_2, seed_3, seed_4],
+    )?;
+
+    let mut bid_escrow = BidEscrow::unpack_unchecked(&bid_escrow_info.try_borrow_data()?)?;
+    if !bid_escrow.is_initialized() {
+        bid_escrow = BidEscrow::new(*nft_auction_info.key, *bidder_info.key, nft_auction.payment_mint);
+    } else if &bid_escrow.nft_auction != nft_auction_info.key || bid_escrow.payment_mint != nft_auction.payment_mint {
+        msg!("Bid escrow is not matched with NFT auction");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    // unclaimed refunds in the escrow are reused for the new bid
+    let amount = required_amount.saturating_sub(bid_escrow.amount);
+    bid_escrow.deposit(amount)?;
+
+    msg!("Deposit into bid escrow: {}", amount);
+
+    // pay to auction
+    if let Some((token_program_info, escrow_info)) = next_escrow_account_infos(account_info_iter, nft_auction)? {
+        let bidder_token_info = next_account_info(account_info_iter)?;
+
+        process_token_transfer(
+            token_program_info,
+            bidder_token_info,
+            escrow_info,
+            bidder_info,
+            &[],
+            amount,
+        )?;
+    } else {
+        process_transfer(
+            bidder_info,
+            nft_auction_authority_info,
+            system_program_info,
+            amount,
+            &[],
+        )?;
+    }
+
+    bid_escrow.pack(&mut bid_escrow_info.try_borrow_mut_data()?)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_record_bid_history<'a>(
+    program_id: &Pubkey,
+    rent_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    nft_auction_info: &AccountInfo<'a>,
+    bidder_info: &AccountInfo<'a>,
+    bid_history_page_infos: &[&AccountInfo<'a>],
+    nft_auction: &mut NFTAuction,
+    bid_info: BidInfo,
+) -> ProgramResult {
+    let page = nft_auction.bid_history_page();
+    let (key, seed_1, seed_2, ref seed_3, ref seed_4)
+        = get_bid_history_page_pda(nft_auction_info.key, page, program_id);
+    let bid_history_page_info = bid_history_page_infos
+        .iter()
+        .find(|bid_history_page_info| bid_history_page_info.key == &key)
+        .ok_or_else(|| {
+            msg!("Bid history page pubkey is an invalid pda pubkey");
+            VoilaError::InvalidPdaPubkey
+        })?;
+
+    process_optimal_create_account(
+        rent_info,
+        bid_history_page_info,
+        bidder_info,
+        system_program_info,
+        program_id,
+        BidHistoryPage::LEN,
+        &[],
+        &[seed_1, seed_2, seed_3, seed_4],
+    )?;
+
+    let mut bid_history_page = BidHistoryPage::unpack_unchecked(&bid_history_page_info.try_borrow_data()?)?;
+    if !bid_history_page.is_initialized() {
+        bid_history_page = BidHistoryPage::new(*nft_auction_info.key, page);
+    }
+    bid_history_page.push(bid_info)?;
+    nft_auction.bid_count = nft_auction.bid_count.checked_add(1).ok_or(VoilaError::MathOverflow)?;
+
+    bid_history_page.pack(&mut bid_history_page_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+fn process_claim_bid_refund(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let bid_escrow_info = next_account_info(account_info_iter)?;
+    let bidder_info = next_account_info(account_info_iter)?;
+
+    let (key, _, _, _, _) = get_bid_escrow_pda(nft_auction_info.key, bidder_info.key, program_id);
+    if bid_escrow_info.key != &key {
+        msg!("Bid escrow pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+    let mut bid_escrow = BidEscrow::unpack(&bid_escrow_info.try_borrow_data()?)?;
+
+    let nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    if nft_auction_authority_info.key != &nft_auction.pda_authority {
+        msg!("NFT auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    let refund_amount = bid_escrow.refund(nft_auction.locked_amount(clock.unix_timestamp, bidder_info.key))?;
+
+    msg!("Claim bid refund from NFT auction: {}", refund_amount);
+
+    let signer_seeds = &nft_auction.authority_signer_seeds(nft_auction_info.key);
+    if let Some((token_program_info, escrow_info)) = next_escrow_account_infos(account_info_iter, &nft_auction)? {
+        let bidder_token_info = next_account_info(account_info_iter)?;
+        check_payment_token_account(bidder_token_info, bidder_info.key, nft_auction.payment_mint.as_ref().unwrap())?;
+
+        process_token_transfer(
+            token_program_info,
+            escrow_info,
+            bidder_token_info,
+            nft_auction_authority_info,
+            signer_seeds,
+            refund_amount,
+        )?;
+    } else {
+        process_transfer(
+            nft_auction_authority_info,
+            bidder_info,
+            system_program_info,
+            refund_amount,
+            signer_seeds,
+        )?;
+    }
+
+    bid_escrow.pack(&mut bid_escrow_info.try_borrow_mut_data()?)
+}
+
+fn process_claim_nft_from_auction(program_id: &Pubkey, accounts: &[AccountInfo], index: u8) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let spl_associated_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let nft_account_info = next_account_info(account_info_iter)?;
+
+    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    if nft_auction_authority_info.key != &nft_auction.pda_authority {
+        msg!("NFT auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    nft_auction.claim(clock.unix_timestamp, owner_info.key, index)?;
+
+    msg!("Claim NFT from acution, name = {}, index = {}", nft_auction.name, index);
+
+    let signer_seeds = &nft_auction.authority_signer_seeds(nft_auction_info.key);
+    if let Some(consignment) = &nft_auction.consignment {
+        let nft_escrow_info = next_account_info(account_info_iter)?;
+
+        process_transfer_consigned_nft(
+            rent_info,
+            system_program_info,
+            token_program_info,
+            spl_associated_program_info,
+            nft_auction_authority_info,
+            owner_info,
+            owner_info,
+            nft_mint_info,
+            nft_account_info,
+            nft_escrow_info,
+            consignment,
+            signer_seeds,
+        )?;
+    } else {
+        process_mint_auction_nft(
+            program_id,
+            rent_info,
+            system_program_info,
+            token_program_info,
+            spl_associated_program_info,
+            nft_auction_authority_info,
+            owner_info,
+            owner_info,
+            nft_mint_info,
+            nft_account_info,
+            index,
+            signer_seeds,
+        )?;
+    }
+
+    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+fn process_settle_nft_auction(program_id: &Pubkey, accounts: &[AccountInfo], index: u8) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let spl_associated_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let cranker_info = next_account_info(account_info_iter)?;
+    let winner_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let nft_account_info = next_account_info(account_info_iter)?;
+
+    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    if nft_auction_authority_info.key != &nft_auction.pda_authority {
+        msg!("NFT auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    let (winner, crank_reward) = nft_auction.crank(clock.unix_timestamp, index, cranker_info.key)?;
+    if winner_info.key != &winner {
+        msg!("Winner account is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+
+    msg!("Settle NFT auction, name = {}, index = {}, winner: {}", nft_auction.name, index, winner);
+
+    let signer_seeds = &nft_auction.authority_signer_seeds(nft_auction_info.key);
+    if let Some(consignment) = &nft_auction.consignment {
+        let nft_escrow_info = next_account_info(account_info_iter)?;
+
+        process_transfer_consigned_nft(
+            rent_info,
+            system_program_info,
+            token_program_info,
+            spl_associated_program_info,
+            nft_auction_authority_info,
+            cranker_info,
+            winner_info,
+            nft_mint_info,
+            nft_account_info,
+            nft_escrow_info,
+            consignment,
+            signer_seeds,
+        )?;
+    } else {
+        process_mint_auction_nft(
+            program_id,
+            rent_info,
+            system_program_info,
+            token_program_info,
+            spl_associated_program_info,
+            nft_auction_authority_info,
+            cranker_info,
+            winner_info,
+            nft_mint_info,
+            nft_account_info,
+            index,
+            signer_seeds,
+        )?;
+    }
+
+    if crank_reward > 0 {
+        msg!("Crank reward: {}", crank_reward);
+
+        if let Some((token_program_info, escrow_info)) = next_escrow_account_infos(account_info_iter, &nft_auction)? {
+            let cranker_token_info = next_account_info(account_info_iter)?;
+            check_payment_token_account(cranker_token_info, cranker_info.key, nft_auction.payment_mint.as_ref().unwrap())?;
+
+            process_token_transfer(
+                token_program_info,
+                escrow_info,
+                cranker_token_info,
+                nft_auction_authority_info,
+                signer_seeds,
+                crank_reward,
+            )?;
+        } else {
+            process_transfer(
+                nft_auction_authority_info,
+                cranker_info,
+                system_program_info,
+                crank_reward,
+                signer_seeds,
+            )?;
+        }
+    }
+
+    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+fn process_reclaim_consigned_nft(accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let spl_associated_program_info = next_account_info(account_info_iter)?;
+    let nft_auction_info = next_account_info(account_info_iter)?;
+    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let seller_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let seller_nft_account_info = next_account_info(account_info_iter)?;
+    let nft_escrow_info = next_account_info(account_info_iter)?;
+
+    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    if nft_auction_authority_info.key != &nft_auction.pda_authority {
+        msg!("NFT auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    if !seller_info.is_signer {
+        msg!("Seller account is not a signer");
+        return Err(VoilaError::InvalidAuthority.into());
+    }
+    nft_auction.reclaim(clock.unix_timestamp, seller_info.key)?;
+
+    msg!("Reclaim consigned NFT from auction, name = {}", nft_auction.name);
+
+    process_transfer_consigned_nft(
+        rent_info,
+        system_program_info,
+        token_program_info,
+        spl_associated_program_info,
+        nft_auction_authority_info,
+        seller_info,
+        seller_info,
+        nft_mint_info,
+        seller_nft_account_info,
+        nft_escrow_info,
+        nft_auction.consignment.as_ref().unwrap(),
+        &nft_auction.authority_signer_seeds(nft_auction_info.key),
+    )?;
+
+    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_transfer_consigned_nft<'a>(
+    rent_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    spl_associated_program_info: &AccountInfo<'a>,
+    nft_auction_authority_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    owner_info: &AccountInfo<'a>,
+    nft_mint_info: &AccountInfo<'a>,
+    nft_account_info: &AccountInfo<'a>,
+    nft_escrow_info: &AccountInfo<'a>,
+    consignment: &Consignment,
+    authority_signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    if nft_mint_info.key != &consignment.mint {
+        msg!("Consigned NFT mint is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    if nft_escrow_info.key != &get_associated_token_address(nft_auction_authority_info.key, nft_mint_info.key) {
+        msg!("NFT escrow account is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    if nft_account_info.key != &get_associated_token_address(owner_info.key, nft_mint_info.key) {
+        msg!("NFT account is not the associated token account of the owner");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+
+    process_create_associated_token_account(
+        rent_info,
+        nft_mint_info,
+        nft_account_info,
+        payer_info,
+        owner_info,
+        token_program_info,
+        system_program_info,
+        spl_associated_program_info,
+        &[],
+    )?;
+
+    process_token_transfer(
+        token_program_info,
+        nft_escrow_info,
+        nft_account_info,
+        nft_auction_authority_info,
+        authority_signer_seeds,
+        1,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_mint_auction_nft<'a>(
+    program_id: &Pubkey,
+    rent_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    spl_associated_program_info: &AccountInfo<'a>,
+    nft_auction_authority_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    owner_info: &AccountInfo<'a>,
+    nft_mint_info: &AccountInfo<'a>,
+    nft_account_info: &AccountInfo<'a>,
+    index: u8,
+    authority_signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let (key, seed_1, ref seed_2, ref seed_3)
+        = get_auction_nft_mint_pda(nft_auction_authority_info.key, index, program_id);
+    if &key != nft_mint_info.key {
+        msg!("NFT mint pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+
+    process_init_token_mint(
+        rent_info,
+        nft_mint_info,
+        payer_info,
+        token_program_info,
+        system_program_info,
+        nft_auction_authority_info.key,
+        0,
+        &[],
+        &[seed_1, seed_2, seed_3],
+    )?;
+
+    process_create_associated_token_account(
+        rent_info,
+        nft_mint_info,
+        nft_account_info,
+        payer_info,
+        owner_info,
+        token_program_info,
+        system_program_info,
+        spl_associated_program_info,
+        &[],
+    )?;
+
+    process_token_mint_to(
+        token_program_info,
+        nft_mint_info,
+        nft_account_info,
+        nft_auction_authority_info,
+        authority_signer_seeds,
+        1,
+    )
+}
+
+#[inline(never)]
+#[allow(clippy::too_many_arguments)]
+fn process_create_dutch_auction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    sn: u16,
+    receipt: Pubkey,
+    start_time: UnixTimestamp,
+    end_time: UnixTimestamp,
+    start_price: u64,
+    floor_price: u64,
+    price_decay: PriceDecay,
+    name: String,
+    uri: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let dutch_auction_info = next_account_info(account_info_iter)?;
+    let admin_authority_info = next_account_info(account_info_iter)?;
+
+    let (key, seed_1, seed_2, ref seed_3, ref seed_4)
+        = get_dutch_auction_pda(admin_authority_info.key, sn, program_id);
+    if dutch_auction_info.key != &key {
+        msg!("Dutch auction info pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+
+    msg!(
+        "Create dutch auction info, sn = {}, start time: {}, end time: {}, start price: {}, floor price: {}, decay: {:?}",
+        sn,
+        start_time,
+        end_time,
+        start_price,
+        floor_price,
+        price_decay,
+    );
+
+    if !is_valid_metadata(&name, &uri, 0) {
+        msg!("Dutch auction name or uri is too long for Metaplex metadata");
+        return Err(VoilaError::InvalidParam.into());
+    }
+
+    let dutch_auction = DutchAuction::new(
+        *admin_authority_info.key,
+        receipt,
+        dutch_auction_info.key,
+        program_id,
+        start_time,
+        end_time,
+        start_price,
+        floor_price,
+        price_decay,
+        name,
+        uri,
+    );
+    if !dutch_auction.is_valid() {
+        msg!("Dutch auction time range or price range is invalid");
+        return Err(VoilaError::InvalidParam.into());
+    }
+
+    process_optimal_create_account(
+        rent_info,
+        dutch_auction_info,
+        admin_authority_info,
+        system_program_info,
+        program_id,
+        DutchAuction::LEN,
+        &[],
+        &[seed_1, seed_2, seed_3, seed_4],
+    )?;
+
+    dutch_auction.initialize(&mut dutch_auction_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+fn process_buy_in_dutch_auction(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let spl_associated_program_info = next_account_info(account_info_iter)?;
+    let dutch_auction_info = next_account_info(account_info_iter)?;
+    let dutch_auction_authority_info = next_account_info(account_info_iter)?;
+    let receipt_info = next_account_info(account_info_iter)?;
+    let buyer_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let nft_account_info = next_account_info(account_info_iter)?;
+
+    let mut dutch_auction = DutchAuction::unpack(&dutch_auction_info.try_borrow_data()?)?;
+    if dutch_auction_authority_info.key != &dutch_auction.pda_authority {
+        msg!("Dutch auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    if receipt_info.key != &dutch_auction.receipt {
+        msg!("Receipt account in dutch auction is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    let price = dutch_auction.buy(clock.unix_timestamp, *buyer_info.key)?;
+
+    msg!("Buy in dutch auction, name = {}, price = {}", dutch_auction.name, price);
+
+    // pay for nft
+    process_transfer(
+        buyer_info,
+        receipt_info,
+        system_program_info,
+        price,
+        &[],
+    )?;
+
+    process_mint_auction_nft(
+        program_id,
+        rent_info,
+        system_program_info,
+        token_program_info,
+        spl_associated_program_info,
+        dutch_auction_authority_info,
+        buyer_info,
+        buyer_info,
+        nft_mint_info,
+        nft_account_info,
+        0,
+        &dutch_auction.authority_signer_seeds(dutch_auction_info.key),
+    )?;
+
+    dutch_auction.pack(&mut dutch_auction_info.try_borrow_mut_data()?)
+}
+
+#[inline(never)]
+#[allow(clippy::too_many_arguments)]
+fn process_create_sealed_auction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    sn: u16,
+    receipt: Pubkey,
+    kind: SealedAuctionKind,
+    start_time: UnixTimestamp,
+    commit_end_time: UnixTimestamp,
+    reveal_end_time: UnixTimestamp,
+    min_price: u64,
+    slash_bps: u16,
+    name: String,
+    uri: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let sealed_auction_info = next_account_info(account_info_iter)?;
+    let admin_authority_info = next_account_info(account_info_iter)?;
+
+    let (key, seed_1, seed_2, ref seed_3, ref seed_4)
+        = get_sealed_auction_pda(admin_authority_info.key, sn, program_id);
+    if sealed_auction_info.key != &key {
+        msg!("Sealed auction info pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+
+    msg!(
+        "Create sealed auction info, sn = {}, kind: {:?}, start time: {}, commit end time: {}, reveal end time: {}, min price: {}, slash bps: {}",
+        sn,
+        kind,
+        start_time,
+        commit_end_time,
+        reveal_end_time,
+        min_price,
+        slash_bps,
+    );
+
+    if !is_valid_metadata(&name, &uri, 0) {
+        msg!("Sealed auction name or uri is too long for Metaplex metadata");
+        return Err(VoilaError::InvalidParam.into());
+    }
+
+    let sealed_auction = SealedAuction::new(
+        *admin_authority_info.key,
+        receipt,
+        sealed_auction_info.key,
+        program_id,
+        kind,
+        start_time,
+        commit_end_time,
+        reveal_end_time,
+        min_price,
+        slash_bps,
+        name,
+        uri,
+    );
+    if !sealed_auction.is_valid() {
+        msg!("Sealed auction time windows or slash bps are invalid");
+        return Err(VoilaError::InvalidParam.into());
+    }
+
+    process_optimal_create_account(
+        rent_info,
+        sealed_auction_info,
+        admin_authority_info,
+        system_program_info,
+        program_id,
+        SealedAuction::LEN,
+        &[],
+        &[seed_1, seed_2, seed_3, seed_4],
+    )?;
+
+    sealed_auction.initialize(&mut sealed_auction_info.try_borrow_mut_data()?)
 }
 
 #[inline(never)]
-fn process_withdraw_from_nft_auction(accounts: &[AccountInfo]) -> ProgramResult {
+fn process_commit_sealed_bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    commitment: [u8; 32],
+    deposit: u64,
+) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
-    let nft_auction_info = next_account_info(account_info_iter)?;
-    let nft_auction_authority_info = next_account_info(account_info_iter)?;
-    let admin_info = next_account_info(account_info_iter)?;
-    let receipt_info = next_account_info(account_info_iter)?;
-
-    let nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
-    if nft_auction_authority_info.key != &nft_auction.pda_authority {
-        msg!("NFT auction authority is not matched with provided");
+    let sealed_auction_info = next_account_info(account_info_iter)?;
+    let sealed_auction_authority_info = next_account_info(account_info_iter)?;
+    let sealed_bid_info = next_account_info(account_info_iter)?;
+    let bidder_info = next_account_info(account_info_iter)?;
+
+    let mut sealed_auction = SealedAuction::unpack(&sealed_auction_info.try_borrow_data()?)?;
+    if sealed_auction_authority_info.key != &sealed_auction.pda_authority {
+        msg!("Sealed auction authority is not matched with provided");
         return Err(VoilaError::UnmatchedAccounts.into());
     }
+    sealed_auction.commit(clock.unix_timestamp, deposit)?;
 
-    if !admin_info.is_signer {
-        msg!("Admin account is not a signer");
-        return Err(VoilaError::InvalidAuthority.into());
-    }
-    if admin_info.key != &nft_auction.admin {
-        msg!("Admin account is not matched with NFT auction admin");
-        return Err(VoilaError::UnmatchedAccounts.into());
+    let (key, seed_1, seed_2, seed_3, ref seed_4)
+        = get_sealed_bid_pda(sealed_auction_info.key, bidder_info.key, program_id);
+    if sealed_bid_info.key != &key {
+        msg!("Sealed bid pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
     }
 
-    msg!("Withdraw from NFT auction");
+    msg!("Commit sealed bid, deposit: {}", deposit);
 
+    process_optimal_create_account(
+        rent_info,
+        sealed_bid_info,
+        bidder_info,
+        system_program_info,
+        program_id,
+        SealedBid::LEN,
+        &[],
+        &[seed_1, seed_2, seed_3, seed_4],
+    )?;
+
+    SealedBid::new(*sealed_auction_info.key, *bidder_info.key, commitment, deposit)
+        .initialize(&mut sealed_bid_info.try_borrow_mut_data()?)?;
+
+    // escrow deposit
     process_transfer(
-        nft_auction_authority_info,
-        receipt_info,
+        bidder_info,
+        sealed_auction_authority_info,
         system_program_info,
-        nft_auction_authority_info.lamports(),
-        &nft_auction.authority_signer_seeds(nft_auction_info.key),
+        deposit,
+        &[],
     )?;
 
-    Ok(())
+    sealed_auction.pack(&mut sealed_auction_info.try_borrow_mut_data()?)
+}
+
+fn check_sealed_bid(
+    program_id: &Pubkey,
+    sealed_bid_info: &AccountInfo,
+    sealed_auction: &Pubkey,
+    bidder: &Pubkey,
+) -> Result<SealedBid, ProgramError> {
+    let (key, _, _, _, _) = get_sealed_bid_pda(sealed_auction, bidder, program_id);
+    if &key != sealed_bid_info.key {
+        msg!("Sealed bid pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+    if sealed_bid_info.owner != program_id {
+        msg!("Sealed bid is not owned by the program");
+        return Err(VoilaError::InvalidAccountOwner.into());
+    }
+
+    let sealed_bid = SealedBid::unpack(&sealed_bid_info.try_borrow_data()?)?;
+    if &sealed_bid.sealed_auction != sealed_auction || &sealed_bid.bidder != bidder {
+        msg!("Sealed bid is not matched with provided auction or bidder");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+
+    Ok(sealed_bid)
+}
+
+#[inline(never)]
+fn process_reveal_sealed_bid(program_id: &Pubkey, accounts: &[AccountInfo], price: u64, salt: [u8; 32]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let sealed_auction_info = next_account_info(account_info_iter)?;
+    let sealed_bid_info = next_account_info(account_info_iter)?;
+    let bidder_info = next_account_info(account_info_iter)?;
+
+    if !bidder_info.is_signer {
+        msg!("Bidder account is not a signer");
+        return Err(VoilaError::InvalidAuthority.into());
+    }
+
+    let mut sealed_auction = SealedAuction::unpack(&sealed_auction_info.try_borrow_data()?)?;
+    let mut sealed_bid = check_sealed_bid(program_id, sealed_bid_info, sealed_auction_info.key, bidder_info.key)?;
+
+    sealed_bid.reveal(price, &salt)?;
+    sealed_auction.reveal(clock.unix_timestamp, *bidder_info.key, price)?;
+
+    msg!("Reveal sealed bid, price: {}", price);
+
+    sealed_bid.pack(&mut sealed_bid_info.try_borrow_mut_data()?)?;
+    sealed_auction.pack(&mut sealed_auction_info.try_borrow_mut_data()?)
 }
 
 #[inline(never)]
-fn process_bid_in_nft_auction(accounts: &[AccountInfo], raise_price: u64) -> ProgramResult {
+fn process_settle_sealed_auction(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
     let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let rent_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
-    let nft_auction_info = next_account_info(account_info_iter)?;
-    let nft_auction_authority_info = next_account_info(account_info_iter)?;
-    let new_bidder_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let spl_associated_program_info = next_account_info(account_info_iter)?;
+    let sealed_auction_info = next_account_info(account_info_iter)?;
+    let sealed_auction_authority_info = next_account_info(account_info_iter)?;
+    let sealed_bid_info = next_account_info(account_info_iter)?;
+    let receipt_info = next_account_info(account_info_iter)?;
+    let cranker_info = next_account_info(account_info_iter)?;
+    let winner_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let nft_account_info = next_account_info(account_info_iter)?;
 
-    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
-    if nft_auction_authority_info.key != &nft_auction.pda_authority {
-        msg!("NFT auction authority is not matched with provided");
+    let mut sealed_auction = SealedAuction::unpack(&sealed_auction_info.try_borrow_data()?)?;
+    if sealed_auction_authority_info.key != &sealed_auction.pda_authority {
+        msg!("Sealed auction authority is not matched with provided");
         return Err(VoilaError::UnmatchedAccounts.into());
     }
-    let last_bid_info= nft_auction.bid(raise_price, clock.unix_timestamp, *new_bidder_info.key)?;
-
-    let latest_price = nft_auction.current_bid_info.as_ref().unwrap().price;
-    msg!("Bid in NFT auction, latest price: {}", latest_price);
+    if receipt_info.key != &sealed_auction.receipt {
+        msg!("Receipt account in sealed auction is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    let mut sealed_bid = check_sealed_bid(program_id, sealed_bid_info, sealed_auction_info.key, winner_info.key)?;
+    let clearing_price = sealed_auction.settle(clock.unix_timestamp, winner_info.key)?;
+    sealed_bid.refunded = true;
 
-    // refund
-    if let Some(last_bid_info) = last_bid_info {
-        let last_bidder_info = next_account_info(account_info_iter)?;
-        if last_bidder_info.key != &last_bid_info.bidder {
-            msg!("Last bidder is not matched with provided");
-            return Err(VoilaError::UnmatchedAccounts.into());
-        }
+    msg!("Settle sealed auction, name = {}, clearing price = {}", sealed_auction.name, clearing_price);
 
-        msg!("Refund to last bidder: {}", last_bid_info.price);
+    let signer_seeds = &sealed_auction.authority_signer_seeds(sealed_auction_info.key);
+    process_transfer(
+        sealed_auction_authority_info,
+        receipt_info,
+        system_program_info,
+        clearing_price,
+        signer_seeds,
+    )?;
 
+    let change = sealed_bid.deposit - clearing_price;
+    if change > 0 {
         process_transfer(
-            nft_auction_authority_info,
-            last_bidder_info,
+            sealed_auction_authority_info,
+            winner_info,
             system_program_info,
-            last_bid_info.price,
-            &nft_auction.authority_signer_seeds(nft_auction_info.key),
+            change,
+            signer_seeds,
         )?;
     }
 
-    // pay to auction
-    process_transfer(
-        new_bidder_info,
-        nft_auction_authority_info,
+    process_mint_auction_nft(
+        program_id,
+        rent_info,
         system_program_info,
-        latest_price,
-        &[],
+        token_program_info,
+        spl_associated_program_info,
+        sealed_auction_authority_info,
+        cranker_info,
+        winner_info,
+        nft_mint_info,
+        nft_account_info,
+        0,
+        signer_seeds,
     )?;
 
-    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+    sealed_bid.pack(&mut sealed_bid_info.try_borrow_mut_data()?)?;
+    sealed_auction.pack(&mut sealed_auction_info.try_borrow_mut_data()?)
 }
 
-fn process_claim_nft_from_auction(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+#[inline(never)]
+fn process_refund_sealed_bid(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
     let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let sealed_auction_info = next_account_info(account_info_iter)?;
+    let sealed_auction_authority_info = next_account_info(account_info_iter)?;
+    let sealed_bid_info = next_account_info(account_info_iter)?;
+    let receipt_info = next_account_info(account_info_iter)?;
+    let bidder_info = next_account_info(account_info_iter)?;
+
+    let sealed_auction = SealedAuction::unpack(&sealed_auction_info.try_borrow_data()?)?;
+    if sealed_auction_authority_info.key != &sealed_auction.pda_authority {
+        msg!("Sealed auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    if receipt_info.key != &sealed_auction.receipt {
+        msg!("Receipt account in sealed auction is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    let mut sealed_bid = check_sealed_bid(program_id, sealed_bid_info, sealed_auction_info.key, bidder_info.key)?;
+    let (refund, slashed) = sealed_bid.refund(clock.unix_timestamp, &sealed_auction)?;
+
+    msg!("Refund sealed bid, refund: {}, slashed: {}", refund, slashed);
+
+    let signer_seeds = &sealed_auction.authority_signer_seeds(sealed_auction_info.key);
+    if refund > 0 {
+        process_transfer(
+            sealed_auction_authority_info,
+            bidder_info,
+            system_program_info,
+            refund,
+            signer_seeds,
+        )?;
+    }
+    if slashed > 0 {
+        process_transfer(
+            sealed_auction_authority_info,
+            receipt_info,
+            system_program_info,
+            slashed,
+            signer_seeds,
+        )?;
+    }
+
+    sealed_bid.pack(&mut sealed_bid_info.try_borrow_mut_data()?)
+}
+
+#[cfg(feature = "metaplex")]
+fn process_bind_auction_nft_on_metaplex(program_id: &Pubkey, accounts: &[AccountInfo], index: u8) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
     let rent_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
-    let spl_associated_program_info = next_account_info(account_info_iter)?;
+    let metaplex_program_info = next_account_info(account_info_iter)?;
     let nft_auction_info = next_account_info(account_info_iter)?;
     let nft_auction_authority_info = next_account_info(account_info_iter)?;
-    let owner_info = next_account_info(account_info_iter)?;
     let nft_mint_info = next_account_info(account_info_iter)?;
-    let nft_account_info = next_account_info(account_info_iter)?;
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let master_edition_account_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
 
-    let mut nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
+    use crate::nft::{
+        metaplex::{
+            process_invoke_metaplex_create_metadata_accounts,
+            process_invoke_metaplex_create_master_edition_accounts,
+            process_invoke_metaplex_sign_metadata,
+        },
+        Meta,
+    };
+
+    check_metaplex_program(metaplex_program_info)?;
+
+    let nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
     if nft_auction_authority_info.key != &nft_auction.pda_authority {
         msg!("NFT auction authority is not matched with provided");
         return Err(VoilaError::UnmatchedAccounts.into());
     }
-    nft_auction.claim(clock.unix_timestamp, owner_info.key)?;
-
-    msg!("Claim NFT from acution, name = {}", nft_auction.name);
-
-    let (key, seed_1, ref seed_2)
-        = get_auction_nft_mint_pda(nft_auction_authority_info.key, program_id);
+    let (key, _, _, _) = get_auction_nft_mint_pda(nft_auction_authority_info.key, index, program_id);
     if &key != nft_mint_info.key {
         msg!("NFT mint pubkey is an invalid pda pubkey");
         return Err(VoilaError::InvalidPdaPubkey.into());
     }
+    let signer_seeds = &nft_auction.authority_signer_seeds(nft_auction_info.key);
+    let data = nft_auction.metadata(index as u16);
 
-    process_init_token_mint(
-        rent_info,
+    // the PDA authority stays the update authority, so holders can not rewrite the royalties
+    process_invoke_metaplex_create_metadata_accounts(
+        metaplex_program_info,
+        metadata_account_info,
         nft_mint_info,
+        nft_auction_authority_info,
+        nft_auction_authority_info,
         owner_info,
-        token_program_info,
         system_program_info,
-        nft_auction_authority_info.key,
-        0,
-        &[],
-        &[seed_1, seed_2],
+        rent_info,
+        data,
+        signer_seeds,
     )?;
 
-    process_create_associated_token_account(
-        rent_info,
+    // verify the PDA authority as creator
+    if nft_auction.royalty.is_some() {
+        process_invoke_metaplex_sign_metadata(
+            metaplex_program_info,
+            metadata_account_info,
+            nft_auction_authority_info,
+            signer_seeds,
+        )?;
+    }
+
+    process_invoke_metaplex_create_master_edition_accounts(
+        metaplex_program_info,
+        metadata_account_info,
+        master_edition_account_info,
         nft_mint_info,
-        nft_account_info,
-        owner_info,
+        nft_auction_authority_info,
+        nft_auction_authority_info,
         owner_info,
         token_program_info,
         system_program_info,
-        spl_associated_program_info,
-        &[],
+        rent_info,
+        None,
+        signer_seeds,
     )?;
 
-    process_token_mint_to(
-        token_program_info,
+    if let Some(collection) = &nft_auction.collection {
+        process_verify_collection(
+            account_info_iter,
+            metaplex_program_info,
+            metadata_account_info,
+            nft_auction_authority_info,
+            owner_info,
+            collection,
+            signer_seeds,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "metaplex")]
+fn process_bind_dutch_auction_nft_on_metaplex(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let metaplex_program_info = next_account_info(account_info_iter)?;
+    let dutch_auction_info = next_account_info(account_info_iter)?;
+    let dutch_auction_authority_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let metadata_account_info = next_account_info(account_info_iter)?;
+    let master_edition_account_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+
+    use crate::nft::{
+        metaplex::{
+            process_invoke_metaplex_create_metadata_accounts,
+            process_invoke_metaplex_create_master_edition_accounts,
+        },
+        Meta,
+    };
+
+    check_metaplex_program(metaplex_program_info)?;
+
+    let dutch_auction = DutchAuction::unpack(&dutch_auction_info.try_borrow_data()?)?;
+    if dutch_auction_authority_info.key != &dutch_auction.pda_authority {
+        msg!("Dutch auction authority is not matched with provided");
+        return Err(VoilaError::UnmatchedAccounts.into());
+    }
+    let (key, _, _, _) = get_auction_nft_mint_pda(dutch_auction_authority_info.key, 0, program_id);
+    if &key != nft_mint_info.key {
+        msg!("NFT mint pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+    let signer_seeds = &dutch_auction.authority_signer_seeds(dutch_auction_info.key);
+    let data = dutch_auction.metadata(0);
+
+    // the PDA authority stays the update authority, so holders can not rewrite the metadata
+    process_invoke_metaplex_create_metadata_accounts(
+        metaplex_program_info,
+        metadata_account_info,
         nft_mint_info,
-        nft_account_info,
-        nft_auction_authority_info,
-        &nft_auction.authority_signer_seeds(nft_auction_info.key),
-        1,
+        dutch_auction_authority_info,
+        dutch_auction_authority_info,
+        owner_info,
+        system_program_info,
+        rent_info,
+        data,
+        signer_seeds,
     )?;
 
-    nft_auction.pack(&mut nft_auction_info.try_borrow_mut_data()?)
+    process_invoke_metaplex_create_master_edition_accounts(
+        metaplex_program_info,
+        metadata_account_info,
+        master_edition_account_info,
+        nft_mint_info,
+        dutch_auction_authority_info,
+        dutch_auction_authority_info,
+        owner_info,
+        token_program_info,
+        system_program_info,
+        rent_info,
+        None,
+        signer_seeds,
+    )
 }
 
 #[cfg(feature = "metaplex")]
-fn process_bind_auction_nft_on_metaplex(accounts: &[AccountInfo]) -> ProgramResult {
+fn process_bind_sealed_auction_nft_on_metaplex(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
     let rent_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
     let metaplex_program_info = next_account_info(account_info_iter)?;
-    let nft_auction_info = next_account_info(account_info_iter)?;
-    let nft_auction_authority_info = next_account_info(account_info_iter)?;
+    let sealed_auction_info = next_account_info(account_info_iter)?;
+    let sealed_auction_authority_info = next_account_info(account_info_iter)?;
     let nft_mint_info = next_account_info(account_info_iter)?;
     let metadata_account_info = next_account_info(account_info_iter)?;
     let master_edition_account_info = next_account_info(account_info_iter)?;
     let owner_info = next_account_info(account_info_iter)?;
 
-    use crate::nft::{metaplex::{process_invoke_metaplex_create_metadata_accounts, process_invoke_metaplex_create_master_edition_accounts}, Meta};
+    use crate::nft::{
+        metaplex::{
+            process_invoke_metaplex_create_metadata_accounts,
+            process_invoke_metaplex_create_master_edition_accounts,
+        },
+        Meta,
+    };
 
-    let nft_auction = NFTAuction::unpack(&nft_auction_info.try_borrow_data()?)?;
-    if nft_auction_authority_info.key != &nft_auction.pda_authority {
-        msg!("NFT auction authority is not matched with provided");
+    check_metaplex_program(metaplex_program_info)?;
+
+    let sealed_auction = SealedAuction::unpack(&sealed_auction_info.try_borrow_data()?)?;
+    if sealed_auction_authority_info.key != &sealed_auction.pda_authority {
+        msg!("Sealed auction authority is not matched with provided");
         return Err(VoilaError::UnmatchedAccounts.into());
     }
-    let signer_seeds = &nft_auction.authority_signer_seeds(nft_auction_info.key);
-    let data = nft_auction.metadata(nft_mint_info.key);
+    let (key, _, _, _) = get_auction_nft_mint_pda(sealed_auction_authority_info.key, 0, program_id);
+    if &key != nft_mint_info.key {
+        msg!("NFT mint pubkey is an invalid pda pubkey");
+        return Err(VoilaError::InvalidPdaPubkey.into());
+    }
+    let signer_seeds = &sealed_auction.authority_signer_seeds(sealed_auction_info.key);
+    let data = sealed_auction.metadata(0);
 
+    // the PDA authority stays the update authority, so holders can not rewrite the metadata
     process_invoke_metaplex_create_metadata_accounts(
         metaplex_program_info,
         metadata_account_info,
         nft_mint_info,
-        nft_auction_authority_info,
+        sealed_auction_authority_info,
+        sealed_auction_authority_info,
         owner_info,
         system_program_info,
         rent_info,
@@ -591,11 +2946,110 @@ fn process_bind_auction_nft_on_metaplex(accounts: &[AccountInfo]) -> ProgramResu
         metadata_account_info,
         master_edition_account_info,
         nft_mint_info,
-        nft_auction_authority_info,
+        sealed_auction_authority_info,
+        sealed_auction_authority_info,
         owner_info,
         token_program_info,
         system_program_info,
         rent_info,
+        None,
         signer_seeds,
     )
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::{system_program, sysvar};
+
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+
+    impl TestAccount {
+        fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+            Self { key, owner, lamports: 0, data }
+        }
+
+        fn info(&mut self, is_signer: bool) -> AccountInfo<'_> {
+            AccountInfo::new(&self.key, is_signer, true, &mut self.lamports, &mut self.data, &self.owner, false, 0)
+        }
+    }
+
+    #[test]
+    fn test_settle_consigned_nft_to_foreign_account() {
+        let program_id = crate::ID;
+        let (seller, winner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let nft_mint = Pubkey::new_unique();
+        let nft_auction_key = Pubkey::new_unique();
+
+        let mut nft_auction = NFTAuction::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            &nft_auction_key,
+            &program_id,
+            0,
+            1000,
+            100,
+            10,
+            0,
+            1,
+            None,
+            None,
+            None,
+            None,
+            Some(Consignment { mint: nft_mint, seller, house_fee_bps: 0 }),
+            Some(5),
+            None,
+            None,
+            "auction".to_string(),
+            "https://voila.test/auction".to_string(),
+        );
+        nft_auction.winning_bids.push(BidInfo { bidder: winner, price: 200, timestamp: 500 });
+        let nft_auction_authority = nft_auction.pda_authority;
+        let mut nft_auction_data = vec![0; NFTAuction::LEN];
+        nft_auction.pack(&mut nft_auction_data).unwrap();
+
+        let mut clock = TestAccount::new(sysvar::clock::ID, sysvar::ID, vec![0; Clock::size_of()]);
+        Clock { unix_timestamp: 2000, ..Clock::default() }.to_account_info(&mut clock.info(false)).unwrap();
+        let mut rent = TestAccount::new(sysvar::rent::ID, sysvar::ID, vec![]);
+        let mut system_program = TestAccount::new(system_program::ID, Pubkey::default(), vec![]);
+        let mut token_program = TestAccount::new(spl_token::ID, Pubkey::default(), vec![]);
+        let mut spl_associated_program = TestAccount::new(spl_associated_token_account::ID, Pubkey::default(), vec![]);
+        let mut nft_auction_account = TestAccount::new(nft_auction_key, program_id, nft_auction_data);
+        let mut nft_auction_authority_account = TestAccount::new(nft_auction_authority, system_program::ID, vec![]);
+        let mut cranker = TestAccount::new(Pubkey::new_unique(), system_program::ID, vec![]);
+        let mut winner_account = TestAccount::new(winner, system_program::ID, vec![]);
+        let mut nft_mint_account = TestAccount::new(nft_mint, spl_token::ID, vec![]);
+        // token account of the cranker instead of the winner's associated token account
+        let mut nft_account = TestAccount::new(Pubkey::new_unique(), spl_token::ID, vec![]);
+        let mut nft_escrow = TestAccount::new(
+            get_associated_token_address(&nft_auction_authority, &nft_mint),
+            spl_token::ID,
+            vec![],
+        );
+
+        let accounts = [
+            clock.info(false),
+            rent.info(false),
+            system_program.info(false),
+            token_program.info(false),
+            spl_associated_program.info(false),
+            nft_auction_account.info(false),
+            nft_auction_authority_account.info(false),
+            cranker.info(true),
+            winner_account.info(false),
+            nft_mint_account.info(false),
+            nft_account.info(false),
+            nft_escrow.info(false),
+        ];
+
+        assert_eq!(
+            process_settle_nft_auction(&program_id, &accounts, 0),
+            Err(VoilaError::UnmatchedAccounts.into()),
+        );
+    }
+}
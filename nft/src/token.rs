@@ -115,3 +115,31 @@ pub fn process_token_mint_to<'a>(
         signer_seeds,
     )
 }
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_token_transfer<'a>(
+    token_program: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+    amount: u64,
+) -> ProgramResult {
+    invoke_optionally_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            source.key,
+            destination.key,
+            authority.key,
+            &[],
+            amount,
+        )?,
+        &[
+            source.clone(),
+            destination.clone(),
+            authority.clone(),
+            token_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
@@ -4,7 +4,17 @@ use js_sys::{Uint8Array, Array};
 use solana_program::{program_pack::Pack, pubkey::Pubkey};
 use spl_token::state::Account;
 use wasm_bindgen::{JsValue, prelude::*};
-use voila_nft::{Packer, key::{KeyInfo, UserKeyRecord}, nft::{CommonNFTInfo, auction::NFTAuction}};
+use voila_nft::{
+    Packer,
+    key::{KeyInfo, UserKeyRecord},
+    nft::{
+        CommonNFTInfo,
+        PurchaseRecord,
+        auction::{NFTAuction, BidEscrow, BidHistoryPage},
+        dutch_auction::DutchAuction,
+        sealed_auction::{SealedAuction, SealedBid},
+    },
+};
 
 #[wasm_bindgen]
 pub fn get_key_info(key_info_data: Uint8Array) -> JsValue {
@@ -39,17 +49,130 @@ pub fn get_common_nft_info(common_nft_data: Uint8Array) -> JsValue {
     JsValue::from_serde(&common_nft).expect("serde serialize")
 }
 
+#[wasm_bindgen]
+pub fn get_purchase_record(purchase_record_data: Uint8Array) -> JsValue {
+    console_error_panic_hook::set_once();
+
+    let purchase_record_data = purchase_record_data.to_vec();
+    let purchase_record = PurchaseRecord::unpack(&purchase_record_data)
+        .expect("purchase record data can not unpack");
+
+    JsValue::from_serde(&purchase_record).expect("serde serialize")
+}
+
 #[wasm_bindgen]
 pub fn get_nft_auction(nft_auction_data: Uint8Array) -> JsValue {
     console_error_panic_hook::set_once();
 
     let nft_auction_data = nft_auction_data.to_vec();
-    let nft_auction = NFTAuction::unpack(&nft_auction_data)
+    let mut nft_auction = NFTAuction::unpack(&nft_auction_data)
         .expect("nft auction data can not unpack");
+    if nft_auction.reserve_price.is_some_and(|reserve_price| reserve_price.hidden) {
+        nft_auction.reserve_price = None;
+    }
 
     JsValue::from_serde(&nft_auction).expect("serde serialize")
 }
 
+#[wasm_bindgen]
+pub fn is_nft_auction_reserve_met(nft_auction_data: Uint8Array) -> bool {
+    console_error_panic_hook::set_once();
+
+    let nft_auction_data = nft_auction_data.to_vec();
+    let nft_auction = NFTAuction::unpack(&nft_auction_data)
+        .expect("nft auction data can not unpack");
+
+    nft_auction.is_reserve_met()
+}
+
+#[wasm_bindgen]
+pub fn get_bid_escrow(bid_escrow_data: Uint8Array) -> JsValue {
+    console_error_panic_hook::set_once();
+
+    let bid_escrow_data = bid_escrow_data.to_vec();
+    let bid_escrow = BidEscrow::unpack(&bid_escrow_data)
+        .expect("bid escrow data can not unpack");
+
+    JsValue::from_serde(&bid_escrow).expect("serde serialize")
+}
+
+#[wasm_bindgen]
+pub fn get_min_next_bid(nft_auction_data: Uint8Array) -> u64 {
+    console_error_panic_hook::set_once();
+
+    let nft_auction_data = nft_auction_data.to_vec();
+    let nft_auction = NFTAuction::unpack(&nft_auction_data)
+        .expect("nft auction data can not unpack");
+
+    nft_auction.min_next_bid().expect("min next bid overflow")
+}
+
+#[wasm_bindgen]
+pub fn get_nft_auction_bid_history_page(nft_auction_data: Uint8Array) -> u32 {
+    console_error_panic_hook::set_once();
+
+    let nft_auction_data = nft_auction_data.to_vec();
+    let nft_auction = NFTAuction::unpack(&nft_auction_data)
+        .expect("nft auction data can not unpack");
+
+    nft_auction.bid_history_page()
+}
+
+#[wasm_bindgen]
+pub fn get_bid_history_page(bid_history_page_data: Uint8Array) -> JsValue {
+    console_error_panic_hook::set_once();
+
+    let bid_history_page_data = bid_history_page_data.to_vec();
+    let bid_history_page = BidHistoryPage::unpack(&bid_history_page_data)
+        .expect("bid history page data can not unpack");
+
+    JsValue::from_serde(&bid_history_page).expect("serde serialize")
+}
+
+#[wasm_bindgen]
+pub fn get_dutch_auction(dutch_auction_data: Uint8Array) -> JsValue {
+    console_error_panic_hook::set_once();
+
+    let dutch_auction_data = dutch_auction_data.to_vec();
+    let dutch_auction = DutchAuction::unpack(&dutch_auction_data)
+        .expect("dutch auction data can not unpack");
+
+    JsValue::from_serde(&dutch_auction).expect("serde serialize")
+}
+
+#[wasm_bindgen]
+pub fn get_dutch_auction_price(dutch_auction_data: Uint8Array, timestamp: i64) -> u64 {
+    console_error_panic_hook::set_once();
+
+    let dutch_auction_data = dutch_auction_data.to_vec();
+    let dutch_auction = DutchAuction::unpack(&dutch_auction_data)
+        .expect("dutch auction data can not unpack");
+
+    dutch_auction.current_price(timestamp)
+}
+
+#[wasm_bindgen]
+pub fn get_sealed_auction(sealed_auction_data: Uint8Array) -> JsValue {
+    console_error_panic_hook::set_once();
+
+    let sealed_auction_data = sealed_auction_data.to_vec();
+    let sealed_auction = SealedAuction::unpack(&sealed_auction_data)
+        .expect("sealed auction data can not unpack");
+
+    JsValue::from_serde(&sealed_auction).expect("serde serialize")
+}
+
+#[wasm_bindgen]
+pub fn get_sealed_bid(sealed_bid_data: Uint8Array) -> JsValue {
+    console_error_panic_hook::set_once();
+
+    let sealed_bid_data = sealed_bid_data.to_vec();
+    let sealed_bid = SealedBid::unpack(&sealed_bid_data)
+        .expect("sealed bid data can not unpack");
+
+    JsValue::from_serde(&sealed_bid).expect("serde serialize")
+}
+
 #[wasm_bindgen]
 pub fn get_user_nft_tokens(owner: Pubkey, nft_tokens: Array) -> JsValue {
     console_error_panic_hook::set_once();